@@ -0,0 +1,48 @@
+use crate::theme::{CellStyle, Theme};
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const CONFIG_FILE: &str = ".tetetris.toml";
+
+// Config is read from user's home directory, it looks like this:
+//
+//   theme = "colorblind"
+//   monochrome = false
+//   cells = "half"
+//
+//   [[themes]]
+//   name = "mine"
+//   ...
+//
+// themes are written the same way the built-in ones are,
+// game works with defaults when there is no config at all
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Option<String>,
+    pub monochrome: bool,
+    pub cells: CellStyle,
+    pub themes: Vec<Theme>,
+}
+
+impl Config {
+    pub fn load() -> Result<Config, Box<dyn Error>> {
+        let path = match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(CONFIG_FILE),
+            None => return Ok(Config::default()),
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(format!("Can't read config {}: {}", path.display(), e).into()),
+        };
+        let config = toml::from_str(&content)
+            .map_err(|e| format!("Config {} is broken: {}", path.display(), e))?;
+        Ok(config)
+    }
+}
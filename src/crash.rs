@@ -0,0 +1,79 @@
+use crate::game::Board;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error::Error;
+use std::fs;
+use std::panic;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+// the first panic is the one reported, later ones
+// most likely happen because of it
+static PANIC: Mutex<Option<String>> = Mutex::new(None);
+// where the crashed game was written to, or why it couldn't be
+static DUMP: Mutex<Option<String>> = Mutex::new(None);
+
+// install replaces the panic message, which would be printed on the
+// alternate screen and lost, with a report kept until the terminal is restored
+pub fn install() {
+    panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown error");
+        let location = match info.location() {
+            Some(l) => l.to_string(),
+            None => "unknown place".to_string(),
+        };
+
+        let backtrace = Backtrace::capture();
+        let backtrace = match backtrace.status() {
+            BacktraceStatus::Captured => backtrace.to_string(),
+            _ => "Run with RUST_BACKTRACE=1 to see how it got there.".to_string(),
+        };
+
+        let mut panic = PANIC.lock().unwrap_or_else(PoisonError::into_inner);
+        if panic.is_none() {
+            *panic = Some(format!(
+                "tetetris crashed: {}\n  at {} in thread '{}'\n{}",
+                message,
+                location,
+                thread::current().name().unwrap_or("unnamed"),
+                backtrace
+            ));
+        }
+    }));
+}
+
+// dump writes the boards of the crashed game to the file,
+// so that the crash can be looked into from the bug report
+pub fn dump(path: &Path, boards: &[&Board]) {
+    let note = match write_dump(path, boards) {
+        Ok(()) => format!(
+            "Game was written to {}, please attach it to the bug report.",
+            path.display()
+        ),
+        Err(e) => format!("Can't write the game to {}: {}", path.display(), e),
+    };
+    *DUMP.lock().unwrap_or_else(PoisonError::into_inner) = Some(note);
+}
+
+fn write_dump(path: &Path, boards: &[&Board]) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_vec_pretty(boards)?)?;
+    Ok(())
+}
+
+// report tells what went wrong if anything panicked,
+// it is taken once the terminal is back to normal
+pub fn report() -> Option<String> {
+    let panic = PANIC
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()?;
+    match DUMP.lock().unwrap_or_else(PoisonError::into_inner).take() {
+        Some(note) => Some(format!("{}\n{}", panic, note)),
+        None => Some(panic),
+    }
+}
@@ -1,50 +1,314 @@
-use std::io;
-use std::sync::mpsc;
-use std::thread;
-use std::time::Duration;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem::ManuallyDrop;
+use std::os::unix::io::FromRawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use termion::event::Key;
 use termion::input::TermRead;
 
+use crate::signals::{Signal, Signals};
+
+// game loop runs at a fixed rate while something moves on the screen
+pub const FRAME: Duration = Duration::from_micros(16_667);
+// reader looks whether it should stop this often
+const POLL: Duration = Duration::from_millis(100);
+
 pub enum Event<I> {
     Input(I),
-    Tick,
+    // frame of the game loop with the time since the previous one
+    Frame(Duration),
+    // step is when autoplay bot makes its next input
+    Step,
+    // terminal window gained or lost focus
+    Focus(bool),
+    Signal(Signal),
+    // terminal can't be read anymore, the game can't go on without it
+    Error(io::Error),
+}
+
+// terminal reports focus changes with these once asked to,
+// termion doesn't know them so they are taken out before the keys
+const FOCUS_IN: &[u8] = b"\x1b[I";
+const FOCUS_OUT: &[u8] = b"\x1b[O";
+
+// FocusReports asks the terminal to report focus changes until dropped
+pub struct FocusReports;
+
+impl FocusReports {
+    pub fn enable() -> io::Result<FocusReports> {
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?1004h")?;
+        stdout.flush()?;
+        Ok(FocusReports)
+    }
 }
 
+impl Drop for FocusReports {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x1b[?1004l");
+        let _ = stdout.flush();
+    }
+}
+
+// Events delivers keys from the terminal and caught signals as they come,
+// frames and bot steps are made up between them when their time comes
 pub struct Events {
     rx: mpsc::Receiver<Event<Key>>,
+    stop: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+
+    tick: Duration,
+    last_frame: Instant,
+    next_frame: Instant,
+    step: Option<Duration>,
+    next_step: Instant,
 }
 
 impl Events {
-    pub fn new(tick: u32) -> Events {
+    pub fn new(step: Option<u32>) -> io::Result<Events> {
+        let signals = Signals::catch()?;
         let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop2 = Arc::clone(&stop);
+        let reader = thread::spawn(move || read_input(&tx, &stop2, &signals));
 
-        let tx2 = tx.clone();
+        Ok(Events::paced(rx, stop, Some(reader), step))
+    }
 
-        thread::spawn(move || {
-            let stdin = io::stdin();
+    // paced makes up frames and steps between events coming from rx
+    fn paced(
+        rx: mpsc::Receiver<Event<Key>>,
+        stop: Arc<AtomicBool>,
+        reader: Option<JoinHandle<()>>,
+        step: Option<u32>,
+    ) -> Events {
+        let now = Instant::now();
+        Events {
+            rx,
+            stop,
+            reader,
 
-            for key in stdin.keys().flatten() {
-                if let Err(e) = tx.send(Event::Input(key)) {
-                    eprintln!("Error while processing input events: {}", e);
-                    return;
+            tick: FRAME,
+            last_frame: now,
+            next_frame: now + FRAME,
+            step: step.map(|s| Duration::from_millis(s.into())),
+            next_step: now,
+        }
+    }
+
+    // set_tick changes the time between frames, starting with the next one
+    pub fn set_tick(&mut self, tick: Duration) {
+        if tick != self.tick {
+            self.tick = tick;
+            self.next_frame = self.last_frame + tick;
+        }
+    }
+
+    // next gives due frames first and keys and signals waiting
+    // after them, bot steps go last so that they can't starve the rest
+    pub fn next(&mut self) -> Event<Key> {
+        loop {
+            let now = Instant::now();
+            if now >= self.next_frame {
+                let dt = now - self.last_frame;
+                self.last_frame = now;
+                // frames missed while the loop was busy are not made up,
+                // time passed is given to the next one anyway
+                self.next_frame = (self.next_frame + self.tick).max(now);
+                return Event::Frame(dt);
+            }
+
+            match self.rx.try_recv() {
+                Ok(event) => return event,
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => return closed(),
+            }
+
+            if let Some(step) = self.step {
+                if now >= self.next_step {
+                    self.next_step = now + step;
+                    return Event::Step;
                 }
             }
-        });
 
-        thread::spawn(move || loop {
-            if let Err(e) = tx2.send(Event::Tick) {
-                eprintln!("Error while processing tick: {}", e);
+            let deadline = match self.step {
+                Some(_) => self.next_frame.min(self.next_step),
+                None => self.next_frame,
+            };
+            let timeout = deadline.saturating_duration_since(now);
+            match self.rx.recv_timeout(timeout) {
+                Ok(event) => return event,
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return closed(),
+            }
+        }
+    }
+
+    // shutdown stops reading the terminal and waits for the reader,
+    // so that nothing is read after the terminal is restored,
+    // signals are left to their default actions after that
+    pub fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn closed() -> Event<Key> {
+    let e = io::Error::new(io::ErrorKind::UnexpectedEof, "terminal input is closed");
+    Event::Error(e)
+}
+
+// read_input reads the terminal and signals until it is told to stop,
+// waiting for them only shortly so that it notices that in time
+fn read_input(tx: &mpsc::Sender<Event<Key>>, stop: &AtomicBool, signals: &Signals) {
+    // stdin is read without its buffer, bytes waiting there
+    // would not wake up the poll
+    let mut stdin = ManuallyDrop::new(unsafe { File::from_raw_fd(libc::STDIN_FILENO) });
+    let mut buf = [0; 256];
+
+    while !stop.load(Ordering::Relaxed) {
+        let read = match wait_input(signals.fd(), POLL) {
+            Ok((keys, caught)) => {
+                if caught {
+                    for s in signals.read() {
+                        if tx.send(Event::Signal(s)).is_err() {
+                            return;
+                        }
+                    }
+                }
+                if !keys {
+                    continue;
+                }
+                stdin.read(&mut buf)
+            }
+            Err(e) => Err(e),
+        };
+        let n = match read {
+            Ok(0) => return,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                let _ = tx.send(Event::Error(e));
                 return;
             }
+        };
 
-            thread::sleep(Duration::from_millis(u64::from(tick)));
-        });
+        if send_input(tx, &buf[..n]).is_err() {
+            return;
+        }
+    }
+}
 
-        Events { rx }
+// send_input sends keys and focus changes in the order they came
+fn send_input(
+    tx: &mpsc::Sender<Event<Key>>,
+    mut bytes: &[u8],
+) -> Result<(), mpsc::SendError<Event<Key>>> {
+    loop {
+        let focus = (0..bytes.len())
+            .find(|&i| bytes[i..].starts_with(FOCUS_IN) || bytes[i..].starts_with(FOCUS_OUT));
+        let keys = &bytes[..focus.unwrap_or(bytes.len())];
+
+        // bytes that are not keys are skipped
+        for key in keys.keys().flatten() {
+            tx.send(Event::Input(key))?;
+        }
+
+        match focus {
+            Some(i) => {
+                tx.send(Event::Focus(bytes[i..].starts_with(FOCUS_IN)))?;
+                bytes = &bytes[i + FOCUS_IN.len()..];
+            }
+            None => return Ok(()),
+        }
     }
+}
+
+// wait_input tells whether stdin and the signal pipe
+// have something to read before the timeout
+fn wait_input(signals: libc::c_int, timeout: Duration) -> io::Result<(bool, bool)> {
+    let mut fds = [libc::STDIN_FILENO, signals].map(|fd| libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    });
+    let ready = unsafe { libc::poll(fds.as_mut_ptr(), 2, timeout.as_millis() as libc::c_int) };
+    if ready < 0 {
+        let e = io::Error::last_os_error();
+        if e.kind() == io::ErrorKind::Interrupted {
+            return Ok((false, false));
+        }
+        return Err(e);
+    }
+    Ok((fds[0].revents != 0, fds[1].revents != 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // next_events counts keys, frames and steps given before the time is up
+    fn next_events(events: &mut Events, wait: Duration) -> (usize, usize, usize) {
+        let (mut keys, mut frames, mut steps) = (0, 0, 0);
+        let deadline = Instant::now() + wait;
+        while Instant::now() < deadline {
+            match events.next() {
+                Event::Input(_) => keys += 1,
+                Event::Frame(_) => frames += 1,
+                Event::Step => steps += 1,
+                _ => {}
+            }
+        }
+        (keys, frames, steps)
+    }
+
+    fn events(step: u32) -> (mpsc::Sender<Event<Key>>, Events) {
+        let (tx, rx) = mpsc::channel();
+        let events = Events::paced(rx, Arc::new(AtomicBool::new(false)), None, Some(step));
+        (tx, events)
+    }
+
+    #[test]
+    fn zero_step_lets_keys_and_frames_through() {
+        let (tx, mut events) = events(0);
+        tx.send(Event::Input(Key::Char('q'))).unwrap();
+
+        let (keys, frames, steps) = next_events(&mut events, FRAME * 3);
+        assert_eq!(keys, 1);
+        assert!(frames >= 2);
+        assert!(steps > 0);
+    }
+
+    #[test]
+    fn step_shorter_than_frame_lets_keys_through() {
+        let (tx, mut events) = events(1);
+        for _ in 0..3 {
+            tx.send(Event::Input(Key::Ctrl('c'))).unwrap();
+        }
+
+        let (keys, frames, _) = next_events(&mut events, FRAME * 3);
+        assert_eq!(keys, 3);
+        assert!(frames >= 2);
+    }
+
+    #[test]
+    fn closed_input_is_an_error() {
+        let (tx, mut events) = events(0);
+        drop(tx);
 
-    pub fn get_event(&self) -> Result<Event<Key>, mpsc::RecvError> {
-        self.rx.recv()
+        assert!(matches!(events.next(), Event::Error(_)));
     }
 }
@@ -0,0 +1,115 @@
+use crate::game::{Board, Type};
+use serde::Serialize;
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Record describes one finished game. It is kept flat,
+// so that every field becomes a column in CSV.
+#[derive(Serialize)]
+pub struct Record {
+    timestamp: u64,
+    mode: &'static str,
+    result: &'static str,
+    seed: u64,
+    score: u64,
+    level: u32,
+    lines: u32,
+    duration: f64,
+    pieces: u32,
+    pieces_i: u32,
+    pieces_l: u32,
+    pieces_t: u32,
+    pieces_s: u32,
+    pieces_o: u32,
+    pieces_j: u32,
+    pieces_z: u32,
+    pps: f64,
+    kpp: f64,
+    attack: u32,
+    apm: f64,
+    singles: u32,
+    doubles: u32,
+    triples: u32,
+    tetrises: u32,
+    max_combo: u32,
+    finesse_faults: u32,
+}
+
+impl Record {
+    pub fn from_board(board: &Board) -> Record {
+        let stats = board.stats();
+        let elapsed = board.elapsed();
+
+        let result = if board.is_completed() {
+            "completed"
+        } else if board.is_game_over() {
+            "game over"
+        } else {
+            "quit"
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Record {
+            timestamp,
+            mode: board.settings().mode.name(),
+            result,
+            seed: board.seed(),
+            score: board.score(),
+            level: board.level(),
+            lines: board.lines(),
+            duration: elapsed.as_secs_f64(),
+            pieces: stats.total_pieces(),
+            pieces_i: stats.pieces(Type::I),
+            pieces_l: stats.pieces(Type::L),
+            pieces_t: stats.pieces(Type::T),
+            pieces_s: stats.pieces(Type::S),
+            pieces_o: stats.pieces(Type::O),
+            pieces_j: stats.pieces(Type::J),
+            pieces_z: stats.pieces(Type::Z),
+            pps: stats.pps(elapsed),
+            kpp: stats.kpp(),
+            attack: stats.attack(),
+            apm: stats.apm(elapsed),
+            singles: stats.singles(),
+            doubles: stats.doubles(),
+            triples: stats.triples(),
+            tetrises: stats.tetrises(),
+            max_combo: stats.max_combo(),
+            finesse_faults: stats.finesse_faults(),
+        }
+    }
+}
+
+// append adds record to the end of the file, creating it if needed.
+// Files ending with '.csv' are written as CSV, anything else as JSON Lines.
+pub fn append(path: &Path, record: &Record) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    let is_csv = path
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+
+    if is_csv {
+        // header goes only to a brand new file
+        let empty = file.metadata()?.len() == 0;
+        let mut w = csv::WriterBuilder::new()
+            .has_headers(empty)
+            .from_writer(file);
+        w.serialize(record)?;
+        w.flush()?;
+    } else {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+    }
+
+    Ok(())
+}
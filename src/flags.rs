@@ -1,9 +1,31 @@
+use crate::theme::CellStyle;
 use clap::{crate_version, App, Arg};
 use std::error::Error;
 use std::fmt;
+use std::path::PathBuf;
 
 pub struct Flags {
     pub tick: u32,
+    pub seed: Option<u64>,
+    pub export: Option<PathBuf>,
+    // delay between bot inputs in miliseconds if bot is playing
+    pub autoplay: Option<u32>,
+    // network game, either as a host on the port or joining the address
+    pub host: Option<u16>,
+    pub join: Option<String>,
+    // address to stream the game to spectators on, or to watch a game at
+    pub publish: Option<String>,
+    pub watch: Option<String>,
+    pub resume: bool,
+    pub fumen: Option<String>,
+    pub puzzle: Option<PathBuf>,
+    pub trainer: Option<PathBuf>,
+    pub setup: Option<String>,
+    pub theme: Option<String>,
+    pub monochrome: bool,
+    pub cells: Option<String>,
+    // file to write the game to if it crashes
+    pub crash_dump: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -37,6 +59,148 @@ pub fn config_flags() -> Result<Flags, ParseError> {
                 .help("Tetris tick rate in miliseconds. Default is '300'.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("seed")
+                .short("s")
+                .long("seed")
+                .help("Random seed for the piece sequence. Random for every game by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("export")
+                .short("e")
+                .long("export")
+                .help(
+                    "File to append a record of every finished game to. \
+                     Written as CSV if file name ends with '.csv', as JSON Lines otherwise.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("autoplay")
+                .short("a")
+                .long("autoplay")
+                .help("Let the bot play instead of the keyboard."),
+        )
+        .arg(
+            Arg::with_name("bot-speed")
+                .long("bot-speed")
+                .help("Delay between bot inputs in miliseconds. Default is '100'.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("host")
+                .long("host")
+                .help("Wait for an opponent to join a network game on the port.")
+                .takes_value(true)
+                .value_name("port")
+                .conflicts_with_all(&["join", "autoplay"]),
+        )
+        .arg(
+            Arg::with_name("join")
+                .long("join")
+                .help("Join a network game hosted at the address, e.g. '192.168.1.2:7777'.")
+                .takes_value(true)
+                .value_name("addr")
+                .conflicts_with("autoplay"),
+        )
+        .arg(
+            Arg::with_name("publish")
+                .long("publish")
+                .help(
+                    "Let others watch the game on the address. \
+                     Addresses with a port are TCP, anything else is a Unix socket path.",
+                )
+                .takes_value(true)
+                .value_name("addr"),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .long("watch")
+                .help("Watch a game published at the address.")
+                .takes_value(true)
+                .value_name("addr")
+                .conflicts_with_all(&["host", "join", "autoplay", "publish"]),
+        )
+        .arg(
+            Arg::with_name("resume")
+                .long("resume")
+                .help("Continue the game saved when leaving it with 'q' or C-c.")
+                .conflicts_with_all(&["host", "join", "autoplay", "watch"]),
+        )
+        .arg(
+            Arg::with_name("fumen")
+                .long("fumen")
+                .help(
+                    "Start a game from a board setup shared as a fumen code or url. \
+                     Codes exported in the game with 'f' are printed on exit.",
+                )
+                .takes_value(true)
+                .value_name("code")
+                .conflicts_with_all(&["host", "join", "watch", "resume"]),
+        )
+        .arg(
+            Arg::with_name("puzzle")
+                .long("puzzle")
+                .help("Play a puzzle: board, pieces and goal are read from the TOML file.")
+                .takes_value(true)
+                .value_name("file")
+                .conflicts_with_all(&["host", "join", "watch", "resume", "fumen"]),
+        )
+        .arg(
+            Arg::with_name("trainer")
+                .long("trainer")
+                .help(
+                    "Practice an opener: its shape is read from the TOML file of setups \
+                     and shown on the board, misplaced cells are marked.",
+                )
+                .takes_value(true)
+                .value_name("file")
+                .conflicts_with_all(&["host", "join", "watch", "resume", "fumen", "puzzle"]),
+        )
+        .arg(
+            Arg::with_name("setup")
+                .long("setup")
+                .help("Name of the setup to practice, the first one in the file by default.")
+                .takes_value(true)
+                .value_name("name")
+                .requires("trainer"),
+        )
+        .arg(
+            Arg::with_name("theme")
+                .long("theme")
+                .help(
+                    "Color theme: default, guideline, classic, high-contrast, colorblind \
+                     or one defined in ~/.tetetris.toml.",
+                )
+                .takes_value(true)
+                .value_name("name"),
+        )
+        .arg(
+            Arg::with_name("monochrome")
+                .long("monochrome")
+                .help("Draw without colors, pieces are told apart by patterns. Same as NO_COLOR."),
+        )
+        .arg(
+            Arg::with_name("cells")
+                .long("cells")
+                .help(
+                    "How cells are drawn. Half and braille fit the board into small terminals, \
+                     the others switch to them when the terminal is too small.",
+                )
+                .takes_value(true)
+                .value_name("style")
+                .possible_values(&CellStyle::NAMES),
+        )
+        .arg(
+            Arg::with_name("crash-dump")
+                .long("crash-dump")
+                .help(
+                    "File to write the game to if tetetris crashes, to attach it to a bug report.",
+                )
+                .takes_value(true)
+                .value_name("file"),
+        )
         .get_matches();
 
     let tick = matches.value_of("tick").unwrap_or("300");
@@ -49,5 +213,64 @@ pub fn config_flags() -> Result<Flags, ParseError> {
         Ok(v) => v,
     };
 
-    Ok(Flags { tick })
+    let seed = match matches.value_of("seed").map(|s| s.parse()) {
+        None => None,
+        Some(Ok(v)) => Some(v),
+        Some(Err(_)) => {
+            return Err(ParseError::new(
+                "Wrong value for 'seed' flag. Must be non-negative integer.",
+            ))
+        }
+    };
+
+    let export = matches.value_of("export").map(PathBuf::from);
+
+    let bot_speed = matches.value_of("bot-speed").unwrap_or("100");
+    let bot_speed = match bot_speed.parse() {
+        Ok(v) if v > 0 => v,
+        _ => {
+            return Err(ParseError::new(
+                "Wrong value for 'bot-speed' flag. Must be positive integer.",
+            ))
+        }
+    };
+    let autoplay = if matches.is_present("autoplay") {
+        Some(bot_speed)
+    } else {
+        None
+    };
+
+    let host = match matches.value_of("host").map(|s| s.parse()) {
+        None => None,
+        Some(Ok(v)) => Some(v),
+        Some(Err(_)) => {
+            return Err(ParseError::new(
+                "Wrong value for 'host' flag. Must be a port number.",
+            ))
+        }
+    };
+
+    let join = matches.value_of("join").map(String::from);
+    let publish = matches.value_of("publish").map(String::from);
+    let watch = matches.value_of("watch").map(String::from);
+
+    Ok(Flags {
+        tick,
+        seed,
+        export,
+        autoplay,
+        host,
+        join,
+        publish,
+        watch,
+        resume: matches.is_present("resume"),
+        fumen: matches.value_of("fumen").map(String::from),
+        puzzle: matches.value_of("puzzle").map(PathBuf::from),
+        trainer: matches.value_of("trainer").map(PathBuf::from),
+        setup: matches.value_of("setup").map(String::from),
+        theme: matches.value_of("theme").map(String::from),
+        monochrome: matches.is_present("monochrome"),
+        cells: matches.value_of("cells").map(String::from),
+        crash_dump: matches.value_of("crash-dump").map(PathBuf::from),
+    })
 }
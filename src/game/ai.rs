@@ -0,0 +1,192 @@
+use super::finesse;
+use super::tetronimos::{Cells, Tetronimo};
+use super::{Board, Move};
+use std::collections::{HashSet, VecDeque};
+
+// weights for the board evaluation, taken from
+// a well known genetic algorithm tuned tetris bot
+const AGGREGATE_HEIGHT: f64 = -0.510066;
+const COMPLETE_LINES: f64 = 0.760666;
+const HOLES: f64 = -0.35663;
+const BUMPINESS: f64 = -0.184483;
+const WELLS: f64 = -0.1;
+// nothing can be worse than topping out
+const TOP_OUT: f64 = -1000.0;
+
+type Grid = [[bool; 10]; 20];
+
+// Placement is a final position of the falling piece
+// together with inputs needed to get there
+pub struct Placement {
+    pub cells: Cells,
+    pub path: Vec<Move>,
+    pub score: f64,
+}
+
+// best_placement evaluates every position falling piece can reach
+// and returns the best one, or None if there is no falling piece
+pub fn best_placement(board: &Board) -> Option<Placement> {
+    let falling = board.falling.as_ref()?;
+    let grid = to_grid(board);
+
+    let mut best: Option<Placement> = None;
+    for (cells, path) in placements(falling, board) {
+        let score = evaluate(&grid, &cells);
+        if best.as_ref().map(|b| score > b.score).unwrap_or(true) {
+            best = Some(Placement { cells, path, score });
+        }
+    }
+
+    best
+}
+
+// placements enumerates all the spots reachable by falling piece,
+// with the shortest path to each of them, ending with a hard drop
+fn placements(falling: &Tetronimo, board: &Board) -> Vec<(Cells, Vec<Move>)> {
+    let rs = board.settings.rotation;
+    let locked = &board.board;
+
+    let mut res = Vec::new();
+    let mut found = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    seen.insert((falling.cells(), falling.rotation()));
+    queue.push_back((falling.clone(), Vec::new()));
+
+    while let Some((t, path)) = queue.pop_front() {
+        let dropped = finesse::dropped(&t, locked);
+        if found.insert(dropped) {
+            let mut path = path.clone();
+            path.push(Move::HardDrop);
+            res.push((dropped, path));
+        }
+
+        let moves = [
+            Move::Left,
+            Move::Right,
+            Move::RotateCw,
+            Move::RotateCcw,
+            Move::Down,
+        ];
+        for m in moves {
+            let mut next = t.clone();
+            let moved = match m {
+                Move::Left => next.move_offset((-1, 0), locked),
+                Move::Right => next.move_offset((1, 0), locked),
+                Move::Down => next.move_offset((0, 1), locked),
+                Move::RotateCw | Move::RotateCcw => {
+                    next.rotate_and_kick(m == Move::RotateCw, locked, rs);
+                    true
+                }
+                Move::HardDrop => false,
+            };
+
+            if moved && seen.insert((next.cells(), next.rotation())) {
+                let mut path = path.clone();
+                path.push(m);
+                queue.push_back((next, path));
+            }
+        }
+    }
+
+    res
+}
+
+fn to_grid(board: &Board) -> Grid {
+    let mut grid = [[false; 10]; 20];
+    for &(x, y) in board.board.keys() {
+        if (0..20).contains(&y) && (0..10).contains(&x) {
+            grid[y as usize][x as usize] = true;
+        }
+    }
+    grid
+}
+
+// evaluate scores the board after the piece is locked in cells,
+// the higher the better
+fn evaluate(grid: &Grid, cells: &Cells) -> f64 {
+    if cells.iter().any(|&(_, y)| y < 0) {
+        return TOP_OUT;
+    }
+
+    let mut grid = *grid;
+    for &(x, y) in cells {
+        grid[y as usize][x as usize] = true;
+    }
+
+    // full rows are removed before the rest is measured
+    let rows: Vec<[bool; 10]> = grid
+        .iter()
+        .filter(|r| !r.iter().all(|c| *c))
+        .copied()
+        .collect();
+    let lines = 20 - rows.len();
+
+    let mut cleared = [[false; 10]; 20];
+    cleared[lines..].copy_from_slice(&rows);
+
+    let mut heights = [0usize; 10];
+    let mut holes = 0;
+    for (col, height) in heights.iter_mut().enumerate() {
+        let top = (0..20).find(|&row| cleared[row][col]);
+        if let Some(top) = top {
+            *height = 20 - top;
+            holes += (top..20).filter(|&row| !cleared[row][col]).count();
+        }
+    }
+
+    let aggregate: usize = heights.iter().sum();
+    let bumpiness: usize = heights
+        .windows(2)
+        .map(|w| (w[0] as i32 - w[1] as i32).unsigned_abs() as usize)
+        .sum();
+
+    // well is a column lower than both of its neighbours,
+    // walls count as infinitely high neighbours
+    let mut wells = 0;
+    for col in 0..10 {
+        let left = if col == 0 { 20 } else { heights[col - 1] };
+        let right = if col == 9 { 20 } else { heights[col + 1] };
+        let depth = left.min(right);
+        if depth > heights[col] {
+            wells += depth - heights[col];
+        }
+    }
+
+    AGGREGATE_HEIGHT * aggregate as f64
+        + COMPLETE_LINES * lines as f64
+        + HOLES * holes as f64
+        + BUMPINESS * bumpiness as f64
+        + WELLS * wells as f64
+}
+
+// Bot plays the game by replaying the path
+// to the best placement one input at a time
+pub struct Bot {
+    plan: VecDeque<Move>,
+    // pieces placed when plan was made, so that
+    // we know when the plan is no longer valid
+    planned_at: Option<u32>,
+}
+
+impl Bot {
+    pub fn new() -> Bot {
+        Bot {
+            plan: VecDeque::new(),
+            planned_at: None,
+        }
+    }
+
+    // next_move returns the next input for the falling piece,
+    // or None if there is no falling piece yet
+    pub fn next_move(&mut self, board: &Board) -> Option<Move> {
+        let placed = board.stats.total_pieces();
+        if self.planned_at != Some(placed) || self.plan.is_empty() {
+            self.plan = best_placement(board)?.path.into();
+            self.planned_at = Some(placed);
+        }
+
+        self.plan.pop_front()
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// Clock measures time spent actually playing,
+// time spent on pause is not counted, instants make no sense
+// outside of this process so clock is saved as the time it shows
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "SavedClock", into = "SavedClock")]
+pub struct Clock {
+    started: Instant,
+    paused_at: Option<Instant>,
+    paused_total: Duration,
+    stopped_at: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            started: Instant::now(),
+            paused_at: None,
+            paused_total: Duration::from_secs(0),
+            stopped_at: None,
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(p) = self.paused_at.take() {
+            self.paused_total += p.elapsed();
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if self.stopped_at.is_none() {
+            self.resume();
+            self.stopped_at = Some(Instant::now());
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped_at.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        // if clock is paused or stopped, time is frozen at that moment
+        let now = match (self.stopped_at, self.paused_at) {
+            (Some(s), _) => s,
+            (None, Some(p)) => p,
+            (None, None) => Instant::now(),
+        };
+
+        now.duration_since(self.started)
+            .checked_sub(self.paused_total)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedClock {
+    elapsed: Duration,
+    paused: bool,
+    stopped: bool,
+}
+
+impl From<Clock> for SavedClock {
+    fn from(c: Clock) -> SavedClock {
+        SavedClock {
+            elapsed: c.elapsed(),
+            paused: c.is_paused(),
+            stopped: c.is_stopped(),
+        }
+    }
+}
+
+impl From<SavedClock> for Clock {
+    fn from(s: SavedClock) -> Clock {
+        let now = Instant::now();
+        let frozen = if s.paused || s.stopped {
+            Some(now)
+        } else {
+            None
+        };
+
+        Clock {
+            started: now.checked_sub(s.elapsed).unwrap_or(now),
+            paused_at: frozen.filter(|_| s.paused),
+            paused_total: Duration::from_secs(0),
+            stopped_at: frozen.filter(|_| s.stopped),
+        }
+    }
+}
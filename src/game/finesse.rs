@@ -0,0 +1,150 @@
+use super::settings::RotationSystem;
+use super::tetronimos::{Cell, Cells, Tetronimo};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// nobody needs more than this many inputs to place a piece,
+// so the search can safely stop there
+const MAX_DEPTH: u32 = 8;
+
+// dropped returns cells the piece would take after a hard drop
+pub fn dropped(t: &Tetronimo, board: &HashMap<(i8, i8), Cell>) -> Cells {
+    let mut t = t.clone();
+    while t.move_offset((0, 1), board) {}
+    t.cells()
+}
+
+// min_inputs finds the least amount of shifts and rotations needed
+// to bring spawned piece into a spot from which hard drop
+// gives the target position. Returns None when the target
+// cannot be reached that way, e.g. it needs a soft drop and a tuck.
+pub fn min_inputs(
+    spawn: &Tetronimo,
+    target: &Tetronimo,
+    board: &HashMap<(i8, i8), Cell>,
+    rs: RotationSystem,
+) -> Option<u32> {
+    let target = target.cells();
+
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    seen.insert((spawn.cells(), spawn.rotation()));
+    queue.push_back((spawn.clone(), 0));
+
+    while let Some((t, depth)) = queue.pop_front() {
+        if dropped(&t, board) == target {
+            return Some(depth);
+        }
+
+        if depth == MAX_DEPTH {
+            continue;
+        }
+
+        for next in moves(&t, board, rs) {
+            if seen.insert((next.cells(), next.rotation())) {
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+
+    None
+}
+
+// moves returns all the pieces reachable from t with one input
+fn moves(t: &Tetronimo, board: &HashMap<(i8, i8), Cell>, rs: RotationSystem) -> Vec<Tetronimo> {
+    let mut res = Vec::with_capacity(4);
+
+    for offset in [(-1, 0), (1, 0)] {
+        let mut next = t.clone();
+        if next.move_offset(offset, board) {
+            res.push(next);
+        }
+    }
+
+    for clockwise in [true, false] {
+        let mut next = t.clone();
+        next.rotate_and_kick(clockwise, board, rs);
+        res.push(next);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tetronimos::Type;
+    use super::*;
+
+    // placed moves a fresh piece with given inputs and drops it
+    fn placed(t: Type, rotations: u32, shift: i8, board: &HashMap<(i8, i8), Cell>) -> Tetronimo {
+        let mut t = Tetronimo::new(t);
+        for _ in 0..rotations {
+            t.rotate_and_kick(true, board, RotationSystem::Srs);
+        }
+        for _ in 0..shift.abs() {
+            assert!(t.move_offset((shift.signum(), 0), board));
+        }
+        while t.move_offset((0, 1), board) {}
+        t
+    }
+
+    #[test]
+    fn shift_to_the_wall() {
+        let board = HashMap::new();
+        let target = placed(Type::I, 0, -3, &board);
+
+        let min = min_inputs(
+            &Tetronimo::new(Type::I),
+            &target,
+            &board,
+            RotationSystem::Srs,
+        );
+        assert_eq!(min, Some(3));
+    }
+
+    #[test]
+    fn rotation_and_shift() {
+        let board = HashMap::new();
+        let target = placed(Type::T, 1, 4, &board);
+
+        let min = min_inputs(
+            &Tetronimo::new(Type::T),
+            &target,
+            &board,
+            RotationSystem::Srs,
+        );
+        assert_eq!(min, Some(5));
+    }
+
+    #[test]
+    fn spawn_position_needs_nothing() {
+        let board = HashMap::new();
+        let target = placed(Type::O, 0, 0, &board);
+
+        let min = min_inputs(
+            &Tetronimo::new(Type::O),
+            &target,
+            &board,
+            RotationSystem::Srs,
+        );
+        assert_eq!(min, Some(0));
+    }
+
+    #[test]
+    fn tuck_under_a_roof_is_out_of_reach() {
+        let board: HashMap<_, _> = (0..4).map(|x| ((x, 17), Cell::Garbage)).collect();
+        let mut target = Tetronimo::new(Type::I);
+        for p in &mut target.pixels {
+            p.x -= 3;
+            p.y = 19;
+        }
+
+        let min = min_inputs(
+            &Tetronimo::new(Type::I),
+            &target,
+            &board,
+            RotationSystem::Srs,
+        );
+        assert_eq!(min, None);
+    }
+}
@@ -0,0 +1,364 @@
+use super::tetronimos::{Cell, Type};
+use std::collections::HashMap;
+use std::error::Error;
+
+// fumen is the format community uses to share board setups,
+// only version 115 and only the first page of it is supported
+
+const VERSION: &str = "v115@";
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// fumen field is 23 rows high with a garbage row below,
+// our 20 rows are the bottom ones, first 3 rows are above our field
+const FIELD_BLOCKS: u32 = 240;
+const ROWS_ABOVE: i8 = 3;
+const GARBAGE_BLOCK: u32 = 8;
+
+// comments are packed 4 printable ascii chars into 5 base64 chars
+const COMMENT_CHARS: u32 = 96;
+const QUIZ_PREFIX: &str = "#Q=";
+
+// Page is what we take from a fumen: locked cells and pieces to play
+pub struct Page {
+    pub cells: HashMap<(i8, i8), Cell>,
+    // current piece first, followed by the queue
+    pub queue: Vec<Type>,
+}
+
+pub fn decode(code: &str) -> Result<Page, Box<dyn Error>> {
+    // code can be given as a whole url, and viewers
+    // put '?' into long codes to break them into lines
+    let start = code
+        .find(VERSION)
+        .ok_or("Only v115 fumen codes are supported")?;
+    let data: Vec<u8> = code[start + VERSION.len()..]
+        .bytes()
+        .filter(|b| *b != b'?')
+        .collect();
+    let mut r = Reader {
+        data: &data,
+        pos: 0,
+    };
+
+    let mut cells = HashMap::new();
+    let mut index = 0;
+    while index < FIELD_BLOCKS {
+        let run = r.read(2)?;
+        let (value, count) = (run / FIELD_BLOCKS, run % FIELD_BLOCKS + 1);
+        if index + count > FIELD_BLOCKS {
+            return Err("Fumen field is broken".into());
+        }
+
+        // values are differences from the empty field shifted by 8,
+        // so that they can't be negative
+        let block = value.checked_sub(8).ok_or("Fumen field is broken")?;
+        for i in index..index + count {
+            let (x, y) = ((i % 10) as i8, (i / 10) as i8 - ROWS_ABOVE);
+            // garbage row below the field is not part of the setup
+            if block == 0 || y >= 20 {
+                continue;
+            }
+            if y < 0 {
+                return Err("Fumen field is higher than 20 rows".into());
+            }
+            cells.insert((x, y), block_cell(block)?);
+        }
+
+        // empty field is followed by the amount of pages it is repeated on
+        if index == 0 && count == FIELD_BLOCKS && block == 0 {
+            r.read(1)?;
+        }
+        index += count;
+    }
+
+    let mut action = r.read(3)?;
+    let piece = action % 8;
+    action /= 8 * 4 * FIELD_BLOCKS;
+    // rise, mirror and color flags go before the comment one
+    let has_comment = (action >> 3) & 1 == 1;
+
+    let mut queue = Vec::new();
+    if has_comment {
+        queue = quiz_queue(&read_comment(&mut r)?)?;
+    }
+    if queue.is_empty() && piece != 0 {
+        queue.push(block_type(piece)?);
+    }
+
+    Ok(Page { cells, queue })
+}
+
+// encode writes locked cells and pieces to play as a single page fumen
+pub fn encode(cells: &HashMap<(i8, i8), Cell>, queue: &[Type]) -> String {
+    let mut w = String::from(VERSION);
+
+    let blocks: Vec<u32> = (0..FIELD_BLOCKS)
+        .map(|i| {
+            let (x, y) = ((i % 10) as i8, (i / 10) as i8 - ROWS_ABOVE);
+            cells.get(&(x, y)).map(cell_block).unwrap_or(0)
+        })
+        .collect();
+
+    let mut index = 0;
+    while index < blocks.len() {
+        let block = blocks[index];
+        let count = blocks[index..].iter().take_while(|b| **b == block).count();
+        write(&mut w, (block + 8) * FIELD_BLOCKS + count as u32 - 1, 2);
+
+        if count == blocks.len() && block == 0 {
+            // field is not repeated on any other page
+            write(&mut w, 0, 1);
+        }
+        index += count;
+    }
+
+    // no piece on the page, just colors enabled and
+    // a comment flag if there are pieces to play
+    let has_comment = !queue.is_empty();
+    let flags = 1 << 2 | u32::from(has_comment) << 3;
+    write(&mut w, flags * FIELD_BLOCKS * 4 * 8, 3);
+
+    if has_comment {
+        let mut quiz = String::from(QUIZ_PREFIX);
+        quiz.push_str("[]");
+        quiz.push_str(&format!("({})", queue[0].name()));
+        for t in &queue[1..] {
+            quiz.push_str(t.name());
+        }
+        write_comment(&mut w, &quiz);
+    }
+
+    w
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    // read returns a number written as little endian base64 chars
+    fn read(&mut self, chars: usize) -> Result<u32, Box<dyn Error>> {
+        let mut value = 0;
+        for i in 0..chars {
+            let c = *self.data.get(self.pos).ok_or("Fumen code is too short")?;
+            let digit = BASE64
+                .iter()
+                .position(|b| *b == c)
+                .ok_or("Fumen code has a wrong character")?;
+            value += (digit as u32) << (6 * i);
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+}
+
+fn write(w: &mut String, mut value: u32, chars: usize) {
+    for _ in 0..chars {
+        w.push(BASE64[(value % 64) as usize] as char);
+        value /= 64;
+    }
+}
+
+fn read_comment(r: &mut Reader) -> Result<String, Box<dyn Error>> {
+    let len = r.read(2)? as usize;
+
+    let mut escaped = String::new();
+    while escaped.len() < len {
+        let mut value = r.read(5)?;
+        for _ in 0..4 {
+            if escaped.len() == len {
+                break;
+            }
+            escaped.push((b' ' + (value % COMMENT_CHARS) as u8) as char);
+            value /= COMMENT_CHARS;
+        }
+    }
+
+    unescape(&escaped)
+}
+
+fn write_comment(w: &mut String, comment: &str) {
+    let escaped = escape(comment);
+    write(w, escaped.len() as u32, 2);
+
+    for chunk in escaped.as_bytes().chunks(4) {
+        let value = chunk
+            .iter()
+            .rev()
+            .fold(0, |v, c| v * COMMENT_CHARS + u32::from(c - b' '));
+        write(w, value, 5);
+    }
+}
+
+// comments are escaped the way javascript escape() does it
+fn escape(s: &str) -> String {
+    let mut res = String::new();
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) {
+            res.push(c);
+        } else if (c as u32) < 256 {
+            res.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            for u in c.encode_utf16(&mut [0; 2]) {
+                res.push_str(&format!("%u{:04X}", u));
+            }
+        }
+    }
+    res
+}
+
+fn unescape(s: &str) -> Result<String, Box<dyn Error>> {
+    let mut units = Vec::new();
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        let (hex, skip) = if let Some(u) = rest.strip_prefix("%u") {
+            (u.get(..4), 6)
+        } else if let Some(x) = rest.strip_prefix('%') {
+            (x.get(..2), 3)
+        } else {
+            units.push(c as u16);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+
+        let hex = hex.ok_or("Fumen comment is broken")?;
+        units.push(u16::from_str_radix(hex, 16)?);
+        rest = &rest[skip..];
+    }
+
+    Ok(String::from_utf16(&units)?)
+}
+
+// quiz_queue reads pieces from a comment like "#Q=[H](C)NEXT",
+// there is no hold in the game so hold piece is ignored
+fn quiz_queue(comment: &str) -> Result<Vec<Type>, Box<dyn Error>> {
+    let quiz = match comment.strip_prefix(QUIZ_PREFIX) {
+        Some(q) => q,
+        None => return Ok(Vec::new()),
+    };
+    let rest = quiz
+        .find(']')
+        .map(|i| &quiz[i + 1..])
+        .ok_or("Fumen quiz is broken")?;
+
+    rest.chars()
+        .filter(|c| *c != '(' && *c != ')')
+        .map(|c| {
+            Type::ALL
+                .iter()
+                .find(|t| t.name().starts_with(c))
+                .copied()
+                .ok_or_else(|| format!("Fumen quiz has unknown piece '{}'", c).into())
+        })
+        .collect()
+}
+
+// pieces are numbered I, L, O, Z, T, J, S in fumen
+const BLOCK_TYPES: [Type; 7] = [
+    Type::I,
+    Type::L,
+    Type::O,
+    Type::Z,
+    Type::T,
+    Type::J,
+    Type::S,
+];
+
+fn block_type(block: u32) -> Result<Type, Box<dyn Error>> {
+    let i = block.checked_sub(1).ok_or("Fumen piece is empty")?;
+    BLOCK_TYPES
+        .get(i as usize)
+        .copied()
+        .ok_or_else(|| "Fumen piece is broken".into())
+}
+
+fn block_cell(block: u32) -> Result<Cell, Box<dyn Error>> {
+    match block {
+        GARBAGE_BLOCK => Ok(Cell::Garbage),
+        b if b > GARBAGE_BLOCK => Err("Fumen field is broken".into()),
+        b => Ok(Cell::Piece(block_type(b)?)),
+    }
+}
+
+fn cell_block(c: &Cell) -> u32 {
+    match c {
+        Cell::Piece(t) => BLOCK_TYPES.iter().position(|b| b == t).unwrap() as u32 + 1,
+        Cell::Garbage => GARBAGE_BLOCK,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // four rows of garbage with a 4 wide well on the right,
+    // on the bottom of the field
+    const WELL: &str = "v115@9gF8DeF8DeF8DeF8NeAgH";
+    // the same field with "#Q=[](T)IO" quiz comment
+    const WELL_QUIZ: &str = "v115@9gF8DeF8DeF8DeF8NeAgWWAFLDmClcJSAVDEHBEooRBUoAVBJHBAA";
+
+    fn well() -> HashMap<(i8, i8), Cell> {
+        let mut cells = HashMap::new();
+        for y in 16..20 {
+            for x in 0..6 {
+                cells.insert((x, y), Cell::Garbage);
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn empty_field() {
+        let page = decode("v115@vhAAgH").unwrap();
+        assert!(page.cells.is_empty());
+        assert!(page.queue.is_empty());
+    }
+
+    #[test]
+    fn garbage_rows() {
+        let page = decode(WELL).unwrap();
+        assert!(page.cells == well());
+        assert!(page.queue.is_empty());
+    }
+
+    #[test]
+    fn quiz_comment() {
+        let page = decode(WELL_QUIZ).unwrap();
+        assert!(page.cells == well());
+        assert!(page.queue == [Type::T, Type::I, Type::O]);
+    }
+
+    #[test]
+    fn url_with_line_breaks() {
+        let url =
+            "https://fumen.zui.jp/?v115@9gF8DeF8?DeF8DeF8NeAgWWAFLDmClcJSAV?DEHBEooRBUoAVBJHBAA";
+        let page = decode(url).unwrap();
+        assert!(page.cells == well());
+        assert!(page.queue == [Type::T, Type::I, Type::O]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut cells = well();
+        cells.insert((6, 19), Cell::Piece(Type::J));
+        cells.insert((7, 19), Cell::Piece(Type::J));
+        cells.insert((8, 19), Cell::Piece(Type::J));
+        cells.insert((8, 18), Cell::Piece(Type::J));
+        cells.insert((0, 0), Cell::Piece(Type::S));
+        let queue = [Type::L, Type::Z, Type::T, Type::I];
+
+        let page = decode(&encode(&cells, &queue)).unwrap();
+        assert!(page.cells == cells);
+        assert!(page.queue == queue);
+
+        assert_eq!(encode(&HashMap::new(), &[]), "v115@vhAAgH");
+    }
+
+    #[test]
+    fn field_higher_than_ours() {
+        // a garbage cell in the top left corner of the fumen field
+        let e = decode("v115@A8uhAgH").err().unwrap();
+        assert_eq!(e.to_string(), "Fumen field is higher than 20 rows");
+    }
+}
@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// lines sent for clearing 0 to 4 rows at once,
+// with and without a T-spin
+const CLEAR_ATTACK: [u32; 5] = [0, 0, 1, 2, 4];
+const TSPIN_ATTACK: [u32; 5] = [0, 2, 4, 6, 6];
+// extra lines for consecutive line clears, the last one is
+// used for all the longer combos
+const COMBO_ATTACK: [u32; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+// extra line for a tetris or T-spin right after another one
+const BACK_TO_BACK_ATTACK: u32 = 1;
+// extra lines for a clear which leaves nothing on the board
+const PERFECT_CLEAR_ATTACK: u32 = 10;
+
+// attack returns lines sent to the opponent for a clear,
+// combo is the amount of line clears in a row before this one
+pub fn attack(rows: u8, tspin: bool, combo: u32, back_to_back: bool, perfect_clear: bool) -> u32 {
+    if rows == 0 {
+        return 0;
+    }
+
+    let rows = usize::from(rows.min(4));
+    let mut lines = if tspin {
+        TSPIN_ATTACK[rows]
+    } else {
+        CLEAR_ATTACK[rows]
+    };
+
+    let combo = (combo as usize).min(COMBO_ATTACK.len() - 1);
+    lines += COMBO_ATTACK[combo];
+
+    if back_to_back {
+        lines += BACK_TO_BACK_ATTACK;
+    }
+    if perfect_clear {
+        lines += PERFECT_CLEAR_ATTACK;
+    }
+
+    lines
+}
+
+// Garbage is a batch of lines received in one attack,
+// arrived is the game time it was received at
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Garbage {
+    pub lines: u32,
+    pub arrived: Duration,
+}
+
+// Queue holds received garbage until it enters the board,
+// player can cancel it with their own attacks before that
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Queue {
+    batches: VecDeque<Garbage>,
+}
+
+impl Queue {
+    pub fn push(&mut self, lines: u32, now: Duration) {
+        if lines > 0 {
+            self.batches.push_back(Garbage {
+                lines,
+                arrived: now,
+            });
+        }
+    }
+
+    // cancel removes up to lines from the oldest batches
+    // and returns what is left of the attack
+    pub fn cancel(&mut self, mut lines: u32) -> u32 {
+        while let Some(g) = self.batches.front_mut() {
+            if lines == 0 {
+                break;
+            }
+
+            let cancelled = lines.min(g.lines);
+            g.lines -= cancelled;
+            lines -= cancelled;
+            if g.lines == 0 {
+                self.batches.pop_front();
+            }
+        }
+
+        lines
+    }
+
+    // take_ready removes batches which have waited for the entry delay
+    pub fn take_ready(&mut self, now: Duration, delay: Duration) -> Vec<Garbage> {
+        let mut ready = Vec::new();
+        while let Some(g) = self.batches.front() {
+            if now < g.arrived + delay {
+                break;
+            }
+            ready.extend(self.batches.pop_front());
+        }
+        ready
+    }
+
+    // lines returns total lines waiting and those of them
+    // that are past the entry delay already
+    pub fn lines(&self, now: Duration, delay: Duration) -> (u32, u32) {
+        let total = self.batches.iter().map(|g| g.lines).sum();
+        let ready = self
+            .batches
+            .iter()
+            .filter(|g| now >= g.arrived + delay)
+            .map(|g| g.lines)
+            .sum();
+        (total, ready)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(500);
+
+    fn at(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn lines(batches: &[Garbage]) -> Vec<u32> {
+        batches.iter().map(|g| g.lines).collect()
+    }
+
+    #[test]
+    fn plain_clears() {
+        assert_eq!(attack(0, false, 0, false, false), 0);
+        assert_eq!(attack(1, false, 0, false, false), 0);
+        assert_eq!(attack(2, false, 0, false, false), 1);
+        assert_eq!(attack(3, false, 0, false, false), 2);
+        assert_eq!(attack(4, false, 0, false, false), 4);
+    }
+
+    #[test]
+    fn tspins() {
+        assert_eq!(attack(1, true, 0, false, false), 2);
+        assert_eq!(attack(2, true, 0, false, false), 4);
+        assert_eq!(attack(3, true, 0, false, false), 6);
+        // T-spin that clears nothing sends nothing
+        assert_eq!(attack(0, true, 0, false, false), 0);
+    }
+
+    #[test]
+    fn combos() {
+        assert_eq!(attack(1, false, 2, false, false), 1);
+        assert_eq!(attack(2, false, 4, false, false), 3);
+        // longest combos all get the last bonus
+        assert_eq!(attack(1, false, 11, false, false), 5);
+        assert_eq!(attack(1, false, 40, false, false), 5);
+    }
+
+    #[test]
+    fn back_to_back() {
+        assert_eq!(attack(4, false, 0, true, false), 5);
+        assert_eq!(attack(2, true, 0, true, false), 5);
+        assert_eq!(attack(3, true, 3, true, false), 8);
+    }
+
+    #[test]
+    fn perfect_clears() {
+        assert_eq!(attack(1, false, 0, false, true), 10);
+        assert_eq!(attack(4, false, 0, false, true), 14);
+        assert_eq!(attack(2, true, 1, true, true), 15);
+    }
+
+    #[test]
+    fn cancel_takes_oldest_lines_first() {
+        let mut queue = Queue::default();
+        queue.push(2, at(0));
+        queue.push(3, at(100));
+        queue.push(4, at(200));
+
+        assert_eq!(queue.cancel(3), 0);
+        assert_eq!(queue.lines(at(0), DELAY), (6, 0));
+        assert_eq!(lines(&queue.take_ready(at(1000), DELAY)), [2, 4]);
+    }
+
+    #[test]
+    fn cancel_returns_what_is_left() {
+        let mut queue = Queue::default();
+        queue.push(2, at(0));
+        queue.push(1, at(100));
+
+        assert_eq!(queue.cancel(5), 2);
+        assert_eq!(queue.lines(at(1000), DELAY), (0, 0));
+        assert_eq!(queue.cancel(4), 4);
+    }
+
+    #[test]
+    fn empty_attack_is_not_queued() {
+        let mut queue = Queue::default();
+        queue.push(0, at(0));
+
+        assert_eq!(queue.lines(at(1000), DELAY), (0, 0));
+        assert!(queue.take_ready(at(1000), DELAY).is_empty());
+    }
+
+    #[test]
+    fn garbage_waits_for_entry_delay() {
+        let mut queue = Queue::default();
+        queue.push(1, at(0));
+        queue.push(2, at(300));
+
+        assert!(queue.take_ready(at(499), DELAY).is_empty());
+        assert_eq!(queue.lines(at(499), DELAY), (3, 0));
+
+        assert_eq!(lines(&queue.take_ready(at(500), DELAY)), [1]);
+        assert_eq!(queue.lines(at(500), DELAY), (2, 0));
+
+        assert_eq!(lines(&queue.take_ready(at(800), DELAY)), [2]);
+        assert_eq!(queue.lines(at(800), DELAY), (0, 0));
+    }
+
+    #[test]
+    fn ready_garbage_keeps_its_order() {
+        let mut queue = Queue::default();
+        queue.push(3, at(0));
+        queue.push(1, at(10));
+        queue.push(2, at(20));
+
+        let ready = queue.take_ready(at(2000), DELAY);
+        assert_eq!(lines(&ready), [3, 1, 2]);
+        assert_eq!(ready[1].arrived, at(10));
+    }
+}
@@ -0,0 +1,117 @@
+use super::Board;
+use std::collections::VecDeque;
+
+// older placements are forgotten, so that long games don't eat all the memory
+const MAX_HISTORY: usize = 200;
+
+// History keeps boards as they were when each of the pieces spawned,
+// undo and redo move along them until a new piece spawns
+#[derive(Clone, Default)]
+pub struct History {
+    boards: VecDeque<Board>,
+    // boards at the end which were undone and can be redone
+    undone: usize,
+}
+
+impl History {
+    // push forgets whatever was undone, as the game went another way
+    pub fn push(&mut self, board: Board) {
+        self.boards.truncate(self.boards.len() - self.undone);
+        self.undone = 0;
+
+        self.boards.push_back(board);
+        if self.boards.len() > MAX_HISTORY {
+            self.boards.pop_front();
+        }
+    }
+
+    // undo returns the board before the last placement, pieces
+    // placed tells whether the piece that spawned last was placed already
+    pub fn undo(&mut self, placed: u32) -> Option<Board> {
+        let current = self.boards.len().checked_sub(self.undone + 1)?;
+
+        let target = if self.boards[current].stats.total_pieces() < placed {
+            current
+        } else {
+            current.checked_sub(1)?
+        };
+
+        self.undone = self.boards.len() - 1 - target;
+        Some(self.boards[target].clone())
+    }
+
+    pub fn redo(&mut self) -> Option<Board> {
+        if self.undone == 0 {
+            return None;
+        }
+
+        self.undone -= 1;
+        Some(self.boards[self.boards.len() - 1 - self.undone].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Settings, Type};
+    use super::*;
+
+    fn board(placed: u32) -> Board {
+        let mut board = Board::new(Settings::new(300));
+        for _ in 0..placed {
+            board.stats.piece_placed(Type::I);
+        }
+        board
+    }
+
+    fn placed(board: Option<Board>) -> Option<u32> {
+        board.map(|b| b.stats.total_pieces())
+    }
+
+    fn history(spawns: u32) -> History {
+        let mut history = History::default();
+        for placed in 0..spawns {
+            history.push(board(placed));
+        }
+        history
+    }
+
+    #[test]
+    fn undo_goes_back_one_spawn_at_a_time() {
+        let mut history = history(3);
+
+        assert_eq!(placed(history.undo(2)), Some(1));
+        assert_eq!(placed(history.undo(1)), Some(0));
+        assert_eq!(placed(history.undo(0)), None);
+    }
+
+    #[test]
+    fn undo_takes_back_piece_placed_without_spawn() {
+        // last piece was placed but the next one never came, game is over
+        let mut history = history(3);
+
+        assert_eq!(placed(history.undo(3)), Some(2));
+        assert_eq!(placed(history.undo(2)), Some(1));
+    }
+
+    #[test]
+    fn redo_walks_forward_to_the_last_spawn() {
+        let mut history = history(3);
+        history.undo(2);
+        history.undo(1);
+
+        assert_eq!(placed(history.redo()), Some(1));
+        assert_eq!(placed(history.redo()), Some(2));
+        assert_eq!(placed(history.redo()), None);
+    }
+
+    #[test]
+    fn new_move_clears_redo() {
+        let mut history = history(3);
+        history.undo(2);
+        history.push(board(2));
+
+        assert_eq!(placed(history.redo()), None);
+        assert_eq!(placed(history.undo(2)), Some(1));
+        assert_eq!(placed(history.undo(1)), Some(0));
+    }
+}
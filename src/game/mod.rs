@@ -1,8 +1,45 @@
+pub mod ai;
+mod clock;
+mod finesse;
+mod fumen;
+mod garbage;
+mod history;
+mod puzzle;
+pub mod settings;
+mod snapshot;
+mod stats;
 mod tetronimos;
-
-use std::collections::HashMap;
-use tetronimos::{Tetronimo, TetronimoBag, Type};
-use tui::style::Color;
+mod trainer;
+
+use clock::Clock;
+use garbage::Queue;
+use history::History;
+use puzzle::Goal;
+pub use puzzle::Puzzle;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use settings::{Mode, Settings};
+pub use snapshot::Snapshot;
+pub use stats::Stats;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::time::Duration;
+pub use tetronimos::{Cell, Pixel, Type};
+use tetronimos::{Cells, Tetronimo, TetronimoBag};
+pub use trainer::Setup;
+use trainer::Target;
+
+const MIN_TICK: u32 = 20;
+// piece resting on the stack locks after the delay, moving or
+// rotating it starts the wait again, but only a few times
+const LOCK_DELAY: Duration = Duration::from_millis(500);
+const LOCK_RESETS: u32 = 15;
+// terminals don't tell when a key is released, shift key is held
+// while its repeats come at least this often, and then the piece
+// moves a cell per repeat interval passed since the last repeat
+const REPEAT_GAP: Duration = Duration::from_millis(50);
+const SHIFT_REPEAT: Duration = Duration::from_millis(20);
 
 pub enum ScoreAction {
     RowCleared(u8),
@@ -16,55 +53,301 @@ pub enum MoveDirection {
     Right,
 }
 
+// Move is any single input player can make on a falling piece
+#[derive(Copy, Clone, PartialEq)]
+pub enum Move {
+    Left,
+    Right,
+    Down,
+    RotateCw,
+    RotateCcw,
+    HardDrop,
+}
+
+// Board is serializable as a whole, so that it can be
+// sent to spectators and saved to continue later
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Board {
     falling: Option<Tetronimo>,
-    upcoming: Option<Tetronimo>,
+    // falling piece as it was spawned and the amount of shifts
+    // and rotations player made with it, used for finesse check
+    spawned: Option<Tetronimo>,
+    piece_inputs: u32,
+    upcoming: VecDeque<Tetronimo>,
 
-    board: HashMap<(i8, i8), Color>,
+    #[serde(with = "locked")]
+    board: HashMap<(i8, i8), Cell>,
 
+    settings: Settings,
+    seed: u64,
     score: u64,
+    lines: u32,
+    level: u32,
+    clock: Clock,
+    stats: Stats,
+
     game_over: bool,
+    // mode goal was reached, e.g. all 40 lines cleared in sprint
+    completed: bool,
     pause: bool,
 
+    // where the bot would put the falling piece, if player asked for it
+    show_hint: bool,
+    hint: Option<Cells>,
+    // player used hints at least once during the game
+    assisted: bool,
+
     bag: TetronimoBag,
+
+    // garbage lines waiting to be added to the board,
+    // and lines this board has sent to the opponent
+    incoming: Queue,
+    outgoing: u32,
+    garbage_rng: ChaCha12Rng,
+    // last piece was rotated into place, needed to tell T-spins
+    last_rotated: bool,
+    tspin: bool,
+    // last line clear was a tetris or a T-spin
+    back_to_back: bool,
+
+    goal: Option<Goal>,
+    // shape built in the trainer
+    target: Option<Target>,
+    // placements which can be undone, only kept in practice modes
+    #[serde(skip)]
+    history: History,
+
+    // time since the falling piece last fell, and since it came to
+    // rest on the stack, both are moved on by update
+    #[serde(skip)]
+    fall_timer: Duration,
+    #[serde(skip)]
+    lock_timer: Option<Duration>,
+    #[serde(skip)]
+    lock_resets: u32,
+    // the last shift key and time since it came
+    #[serde(skip)]
+    shift: Option<(Move, Duration)>,
 }
 
 impl Board {
-    pub fn new() -> Board {
-        Board {
+    pub fn new(settings: Settings) -> Board {
+        let seed = settings.seed.unwrap_or_else(|| thread_rng().gen());
+
+        let mut board = Board {
             falling: None,
-            upcoming: None,
+            spawned: None,
+            piece_inputs: 0,
+            upcoming: VecDeque::new(),
 
             board: HashMap::new(),
+            settings,
+            seed,
             score: 0,
+            lines: 0,
+            level: settings.level,
+            clock: Clock::new(),
+            stats: Stats::new(),
+
             game_over: false,
+            completed: false,
             pause: false,
 
-            bag: TetronimoBag::new(),
+            show_hint: false,
+            hint: None,
+            assisted: false,
+
+            bag: TetronimoBag::new(seed),
+
+            incoming: Queue::default(),
+            outgoing: 0,
+            // garbage should be the same for the same seed, too
+            garbage_rng: ChaCha12Rng::seed_from_u64(seed.wrapping_add(1)),
+            last_rotated: false,
+            tspin: false,
+            back_to_back: false,
+
+            goal: None,
+            target: None,
+            history: History::default(),
+
+            fall_timer: Duration::ZERO,
+            lock_timer: None,
+            lock_resets: 0,
+            shift: None,
+        };
+
+        if settings.mode == Mode::Dig {
+            // every dig row gets its own hole
+            for _ in 0..settings::DIG_ROWS {
+                board.push_garbage(1);
+            }
         }
+
+        board
+    }
+
+    // from_fumen starts a game from a shared setup,
+    // pieces it has are played before the ones from the bag
+    pub fn from_fumen(settings: Settings, code: &str) -> Result<Board, Box<dyn Error>> {
+        let page = fumen::decode(code)?;
+
+        let mut board = Board::new(settings);
+        board.board = page.cells;
+        board.upcoming = page.queue.into_iter().map(Tetronimo::new).collect();
+        Ok(board)
+    }
+
+    // from_puzzle starts a puzzle game, only the pieces
+    // from the puzzle are played
+    pub fn from_puzzle(settings: Settings, puzzle: &Puzzle) -> Result<Board, Box<dyn Error>> {
+        let settings = Settings {
+            mode: Mode::Puzzle,
+            ..settings
+        };
+
+        let mut board = Board::new(settings);
+        board.board = puzzle.cells()?;
+        board.bag = TetronimoBag::scripted(puzzle.pieces()?);
+        board.goal = Some(puzzle.goal().clone());
+        Ok(board)
+    }
+
+    // from_setup starts the trainer on an empty board,
+    // pieces come from the bag as usual
+    pub fn from_setup(settings: Settings, setup: &Setup) -> Result<Board, Box<dyn Error>> {
+        let settings = Settings {
+            mode: Mode::Trainer,
+            ..settings
+        };
+
+        let mut board = Board::new(settings);
+        board.target = Some(setup.target()?);
+        Ok(board)
+    }
+
+    // to_fumen exports locked cells, falling piece and previews
+    pub fn to_fumen(&self) -> String {
+        let queue: Vec<Type> = self
+            .falling
+            .iter()
+            .chain(&self.upcoming)
+            .map(Tetronimo::kind)
+            .collect();
+        fumen::encode(&self.board, &queue)
+    }
+
+    // push_garbage lifts the stack and fills bottom rows with garbage,
+    // all of them have a hole in the same random column
+    fn push_garbage(&mut self, rows: i8) {
+        let lifted: HashMap<(i8, i8), Cell> = self
+            .board
+            .drain()
+            .map(|((x, y), c)| ((x, y - rows), c))
+            .collect();
+
+        // stack pushed out of the field tops the player out
+        if lifted.keys().any(|(_, y)| *y < 0) {
+            self.game_over = true;
+            self.clock.stop();
+        }
+        self.board = lifted;
+
+        let hole = self.garbage_rng.gen_range(0..10);
+        for row in (20 - rows)..20 {
+            for col in (0..10).filter(|c| *c != hole) {
+                self.board.insert((col, row), Cell::Garbage);
+            }
+        }
+    }
+
+    pub fn receive_garbage(&mut self, lines: u32) {
+        self.incoming.push(lines, self.clock.elapsed());
+    }
+
+    // take_attack returns lines to be sent to the opponent
+    // since the last call
+    pub fn take_attack(&mut self) -> u32 {
+        std::mem::take(&mut self.outgoing)
     }
 
     pub fn move_tetrinomo(&mut self, direction: MoveDirection) {
         if self.is_paused() {
             return;
         }
+        self.count_key();
 
         match direction {
             MoveDirection::Down => self.move_down(),
-            MoveDirection::Left => self.move_left(),
-            MoveDirection::Right => self.move_right(),
+            MoveDirection::Left => {
+                self.piece_inputs += 1;
+                self.move_left()
+            }
+            MoveDirection::Right => {
+                self.piece_inputs += 1;
+                self.move_right()
+            }
+        }
+    }
+
+    // press is a move from the keyboard, a held shift key moves
+    // the piece as often as the shift repeat allows, however slowly
+    // the terminal repeats it
+    pub fn press(&mut self, m: Move) {
+        if m != Move::Left && m != Move::Right {
+            self.shift = None;
+            self.apply(m);
+            return;
+        }
+
+        let times = match self.shift {
+            Some((held, since)) if held == m && since < REPEAT_GAP => {
+                (since.as_millis() / SHIFT_REPEAT.as_millis()).max(1)
+            }
+            _ => 1,
+        };
+        self.shift = Some((m, Duration::ZERO));
+        self.apply(m);
+
+        // catching up moves are the same key press, they are not
+        // counted as keys or inputs again
+        if self.is_paused() {
+            return;
+        }
+        for _ in 1..times {
+            match m {
+                Move::Left => self.move_left(),
+                _ => self.move_right(),
+            }
+        }
+    }
+
+    pub fn apply(&mut self, m: Move) {
+        match m {
+            Move::Left => self.move_tetrinomo(MoveDirection::Left),
+            Move::Right => self.move_tetrinomo(MoveDirection::Right),
+            Move::Down => self.move_tetrinomo(MoveDirection::Down),
+            Move::RotateCw => self.rotate(true),
+            Move::RotateCcw => self.rotate(false),
+            Move::HardDrop => self.hard_drop(),
         }
     }
 
     fn move_left(&mut self) {
         if let Some(t) = &mut self.falling {
-            t.move_offset((-1, 0), &self.board);
+            if t.move_offset((-1, 0), &self.board) {
+                self.last_rotated = false;
+                self.reset_lock();
+            }
         }
     }
 
     fn move_right(&mut self) {
         if let Some(t) = &mut self.falling {
-            t.move_offset((1, 0), &self.board);
+            if t.move_offset((1, 0), &self.board) {
+                self.last_rotated = false;
+                self.reset_lock();
+            }
         }
     }
 
@@ -72,6 +355,7 @@ impl Board {
         if let Some(t) = &mut self.falling {
             if t.move_offset((0, 1), &self.board) {
                 t.inc_dropped();
+                self.last_rotated = false;
             }
         }
     }
@@ -80,50 +364,151 @@ impl Board {
         if self.is_paused() {
             return;
         }
+        self.count_key();
+        self.piece_inputs += 1;
 
         if let Some(t) = &mut self.falling {
-            t.rotate_and_kick(clockwise, &self.board);
+            let before = t.cells();
+            t.rotate_and_kick(clockwise, &self.board, self.settings.rotation);
+            if t.cells() != before {
+                self.last_rotated = true;
+                self.reset_lock();
+            }
+        }
+    }
+
+    // reset_lock gives a resting piece which was moved more time
+    fn reset_lock(&mut self) {
+        if self.lock_timer.is_some() && self.lock_resets < LOCK_RESETS {
+            self.lock_timer = Some(Duration::ZERO);
+            self.lock_resets += 1;
+        }
+    }
+
+    fn is_resting(&self) -> bool {
+        self.falling.as_ref().is_some_and(|t| {
+            let mut t = t.clone();
+            !t.move_offset((0, 1), &self.board)
+        })
+    }
+
+    // only keys which act on a falling piece count towards keys per piece
+    fn count_key(&mut self) {
+        if self.falling.is_some() {
+            self.stats.key_pressed();
         }
     }
 
-    fn next_tetronimo_type(&mut self) -> Type {
+    fn next_tetronimo_type(&mut self) -> Option<Type> {
         self.bag.draw_next()
     }
 
-    // return true in case tetronimo was stuck
-    pub fn tick(&mut self) -> bool {
+    // update moves the game on by the time passed since the last
+    // frame, returns true when the falling piece was locked
+    pub fn update(&mut self, dt: Duration) -> bool {
         if self.game_over || self.pause {
-            // if game over or paused, don't make anything fall
-            // just ignore the ticks
+            // if game over or paused, nothing moves
             return false;
         }
-        match &mut self.falling {
-            Some(t) => {
-                // if cannot move anymore
-                if !t.move_offset((0, 1), &self.board) {
-                    self.lock_piece();
-                    return true;
-                }
-            }
+
+        if self.settings.mode == Mode::Ultra
+            && self.clock.elapsed() >= Duration::from_secs(settings::ULTRA_SECONDS)
+        {
+            self.complete();
+            return false;
+        }
+
+        if let Some((_, since)) = &mut self.shift {
+            *since += dt;
+        }
+
+        let interval = Duration::from_millis(self.tick_interval().into());
+        let t = match &mut self.falling {
+            Some(t) => t,
             None => {
-                if let Some(_) = self.upcoming {
-                    self.falling = self.upcoming.take();
-                } else {
-                    self.falling = Some(Tetronimo::new(self.next_tetronimo_type()));
-                }
+                self.spawn();
+                return false;
+            }
+        };
+
+        self.fall_timer += dt;
+        while self.fall_timer >= interval {
+            self.fall_timer -= interval;
+            if !t.move_offset((0, 1), &self.board) {
+                self.fall_timer = Duration::ZERO;
+                break;
+            }
+            self.last_rotated = false;
+        }
 
-                self.upcoming = Some(Tetronimo::new(self.next_tetronimo_type()));
+        if !self.is_resting() {
+            self.lock_timer = None;
+            return false;
+        }
+        let rested = self.lock_timer.get_or_insert(Duration::ZERO);
+        *rested += dt;
+        if *rested < LOCK_DELAY {
+            return false;
+        }
+
+        self.lock_piece();
+        true
+    }
+
+    // spawn lets garbage in and takes the next piece
+    fn spawn(&mut self) {
+        let now = self.clock.elapsed();
+        let delay = Duration::from_millis(self.settings.garbage_delay.into());
+        for g in self.incoming.take_ready(now, delay) {
+            self.push_garbage(g.lines.min(20) as i8);
+            if self.game_over {
+                return;
             }
         }
 
-        false
+        self.falling = match self.upcoming.pop_front() {
+            Some(t) => Some(t),
+            None => self.next_tetronimo_type().map(Tetronimo::new),
+        };
+        if self.falling.is_none() {
+            // puzzle pieces ran out before the goal was reached
+            self.game_over = true;
+            self.clock.stop();
+            return;
+        }
+        self.spawned = self.falling.clone();
+        self.piece_inputs = 0;
+        self.fall_timer = Duration::ZERO;
+        self.lock_timer = None;
+        self.lock_resets = 0;
+        self.update_hint();
+
+        while self.upcoming.len() < self.settings.preview {
+            match self.next_tetronimo_type() {
+                Some(t) => self.upcoming.push_back(Tetronimo::new(t)),
+                None => break,
+            }
+        }
+
+        if self.settings.mode.is_practice() {
+            let history = std::mem::take(&mut self.history);
+            let snapshot = self.clone();
+            self.history = history;
+            self.history.push(snapshot);
+        }
     }
 
     pub fn hard_drop(&mut self) {
+        if self.is_paused() {
+            return;
+        }
+        self.count_key();
+
         if let Some(t) = self.falling.as_mut() {
             let mut rows_dropped = 0;
             while t.move_offset((0, 1), &self.board) {
                 rows_dropped += 1;
+                self.last_rotated = false;
             }
             self.lock_piece();
             self.add_score(ScoreAction::HardDrop(rows_dropped));
@@ -131,36 +516,300 @@ impl Board {
     }
 
     fn lock_piece(&mut self) {
+        self.check_finesse();
+        self.tspin = self.is_tspin();
+
         let t = self.falling.as_ref().unwrap();
+        if let Some(target) = &mut self.target {
+            target.check(t.kind(), &t.pixels, &self.board);
+        }
         for p in t.pixels {
             if p.y < 0 {
                 // piece couldn't move and part of it was above the screen
                 // is how game over is determined for tetris
                 self.game_over = true;
             }
-            self.board.insert((p.x, p.y), p.c);
+            self.board.insert((p.x, p.y), Cell::Piece(t.kind()));
         }
         let fall = t.dropped();
+        self.stats.piece_placed(t.kind());
         self.add_score(ScoreAction::FallLength(fall));
         self.falling = None;
+        self.hint = None;
+
+        if self.game_over {
+            if self.settings.mode == Mode::Zen {
+                // zen never ends, we just start over with an empty board
+                self.board.clear();
+                self.game_over = false;
+            } else {
+                self.clock.stop();
+            }
+        }
+
+        if let Some(target) = &self.target {
+            // rows of the shape may be full, so it is checked
+            // before they are cleared
+            if !self.game_over && target.is_built(&self.board) {
+                self.complete();
+            }
+        }
+    }
+
+    // is_tspin tells whether the falling T piece was rotated into place
+    // with at least three corners around its center blocked
+    fn is_tspin(&self) -> bool {
+        let t = match &self.falling {
+            Some(t) if t.kind() == Type::T && self.last_rotated => t,
+            _ => return false,
+        };
+
+        let center = t.pixels[0];
+        let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+        let blocked = corners
+            .iter()
+            .map(|(dx, dy)| (center.x + dx, center.y + dy))
+            .filter(|&(x, y)| !(0..10).contains(&x) || y >= 20 || self.board.contains_key(&(x, y)))
+            .count();
+
+        blocked >= 3
+    }
+
+    // check_finesse compares inputs player made for the falling piece
+    // with the least inputs needed to put it where it is now
+    fn check_finesse(&mut self) {
+        let (spawned, t) = match (self.spawned.take(), &self.falling) {
+            (Some(s), Some(t)) => (s, t),
+            _ => return,
+        };
+
+        let rs = self.settings.rotation;
+        if let Some(min) = finesse::min_inputs(&spawned, t, &self.board, rs) {
+            if self.piece_inputs > min {
+                self.stats.finesse_fault(self.piece_inputs - min);
+            }
+        }
+    }
+
+    // win is how opponent tells this board that it has won
+    pub fn win(&mut self) {
+        if !self.game_over {
+            self.complete();
+        }
+    }
+
+    fn complete(&mut self) {
+        self.completed = true;
+        self.game_over = true;
+        self.falling = None;
+        self.clock.stop();
+    }
+
+    // rows_cleared is called once all the full rows
+    // after a piece lock are deleted
+    pub fn rows_cleared(&mut self, amount: u8) {
+        self.add_score(ScoreAction::RowCleared(amount));
+        self.stats.rows_cleared(amount);
+        self.lines += u32::from(amount);
+        if self.tspin {
+            self.stats.tspin_cleared(amount);
+        }
+
+        if amount > 0 {
+            let difficult = amount >= 4 || self.tspin;
+            let combo = self.stats.combo().max(0) as u32;
+            let back_to_back = difficult && self.back_to_back;
+            self.back_to_back = difficult;
+
+            // rows are deleted by now, nothing left means a perfect clear
+            let perfect_clear = self.board.is_empty();
+
+            // attack cancels our own incoming garbage first
+            let lines = garbage::attack(amount, self.tspin, combo, back_to_back, perfect_clear);
+            self.stats.attack_sent(lines);
+            self.outgoing += self.incoming.cancel(lines);
+        }
+
+        match self.settings.mode {
+            Mode::Marathon => {
+                let level = self.settings.level + self.lines / 10;
+                self.level = level.min(settings::MAX_LEVEL);
+            }
+            Mode::Sprint => {
+                if self.lines >= settings::SPRINT_LINES {
+                    self.complete();
+                }
+            }
+            Mode::Dig => {
+                if !self.board.values().any(|c| *c == Cell::Garbage) {
+                    self.complete();
+                }
+            }
+            Mode::Puzzle => {
+                if self.goal_reached() {
+                    self.complete();
+                }
+            }
+            Mode::Ultra | Mode::Zen | Mode::Versus | Mode::Trainer => {}
+        }
+    }
+
+    fn goal_reached(&self) -> bool {
+        let goal = match &self.goal {
+            Some(g) => g,
+            None => return false,
+        };
+
+        // rows are cleared after every lock, the goal without conditions
+        // is only reached once the last piece is placed
+        if goal.is_empty() {
+            return self.falling.is_none() && self.upcoming.is_empty() && self.bag.is_empty();
+        }
+
+        self.lines >= goal.lines
+            && goal.tspin.map(|r| self.stats.tspins(r) > 0).unwrap_or(true)
+            && (!goal.perfect_clear || self.board.is_empty())
+    }
+
+    pub fn goal(&self) -> Option<&Goal> {
+        self.goal.as_ref()
+    }
+
+    // target_pixel is the piece the trainer shape wants in the cell
+    pub fn target_pixel(&self, x: i8, y: i8) -> Option<Type> {
+        self.target.as_ref()?.get(x, y)
     }
 
-    pub fn check_pixel(&self, x: i8, y: i8) -> Option<Color> {
+    pub fn is_mistake(&self, x: i8, y: i8) -> bool {
+        self.target.as_ref().is_some_and(|t| t.is_mistake(x, y))
+    }
+
+    pub fn target(&self) -> Option<&Target> {
+        self.target.as_ref()
+    }
+
+    // undo goes back to the moment the last placed piece spawned
+    pub fn undo(&mut self) {
+        if let Some(b) = self.history.undo(self.stats.total_pieces()) {
+            self.restore(b);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(b) = self.history.redo() {
+            self.restore(b);
+        }
+    }
+
+    fn restore(&mut self, board: Board) {
+        let history = std::mem::take(&mut self.history);
+        *self = board;
+        self.history = history;
+    }
+
+    pub fn check_pixel(&self, x: i8, y: i8) -> Option<Cell> {
         if let Some(t) = &self.falling {
-            if let Some(c) = t.check_pixel(x, y) {
-                return Some(c);
+            if let Some(t) = t.check_pixel(x, y) {
+                return Some(Cell::Piece(t));
             }
         }
 
         self.board.get(&(x, y)).copied()
     }
 
+    // field is the whole board with the falling piece in it,
+    // rows go first so that it is read line by line
+    pub fn field(&self) -> [[Option<Cell>; 10]; 20] {
+        let mut field = [[None; 10]; 20];
+        let locked = self.board.iter().map(|(&(x, y), &c)| (x, y, c));
+        let falling = self.falling.iter().flat_map(|t| {
+            let c = Cell::Piece(t.kind());
+            t.pixels.iter().map(move |p| (p.x, p.y, c))
+        });
+
+        for (x, y, c) in locked.chain(falling) {
+            if (0..10).contains(&x) && (0..20).contains(&y) {
+                field[y as usize][x as usize] = Some(c);
+            }
+        }
+        field
+    }
+
+    // check is done on boards read from elsewhere,
+    // they are drawn without any further checks
+    pub fn check(&self) -> Result<(), String> {
+        self.settings.check()?;
+        match &self.goal {
+            Some(goal) => goal.check().map_err(|e| format!("Puzzle goal {}", e)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let mut cells = Vec::new();
+        for y in 0..20 {
+            for x in 0..10 {
+                if let Some(c) = self.check_pixel(x, y) {
+                    cells.push((x, y, c));
+                }
+            }
+        }
+
+        Snapshot {
+            cells,
+            score: self.score,
+            lines: self.lines,
+            incoming: self.incoming().0,
+            game_over: self.game_over,
+            completed: self.completed,
+        }
+    }
+
+    pub fn toggle_hint(&mut self) {
+        self.show_hint = !self.show_hint;
+        self.assisted |= self.show_hint;
+        self.update_hint();
+    }
+
+    // hint is computed once per piece, from its spawn position
+    fn update_hint(&mut self) {
+        self.hint = if self.show_hint {
+            ai::best_placement(self).map(|p| p.cells)
+        } else {
+            None
+        };
+    }
+
+    // hint_pixel returns type of the falling piece
+    // if hint suggests to put it on this cell
+    pub fn hint_pixel(&self, x: i8, y: i8) -> Option<Type> {
+        let hint = self.hint.as_ref()?;
+        let t = self.falling.as_ref()?;
+
+        if hint.contains(&(x, y)) {
+            Some(t.kind())
+        } else {
+            None
+        }
+    }
+
+    // incoming returns all the garbage lines waiting
+    // and those of them that enter with the next piece
+    pub fn incoming(&self) -> (u32, u32) {
+        let delay = Duration::from_millis(self.settings.garbage_delay.into());
+        self.incoming.lines(self.clock.elapsed(), delay)
+    }
+
+    pub fn is_assisted(&self) -> bool {
+        self.assisted
+    }
+
     pub fn can_delete(&self) -> Option<i8> {
         for row in (0..20).rev() {
             let mut all_filled = true;
 
             for col in 0..10 {
-                if self.board.get(&(col, row)).is_none() {
+                if !self.board.contains_key(&(col, row)) {
                     all_filled = false;
                     break;
                 }
@@ -182,47 +831,174 @@ impl Board {
     pub fn collapse(&mut self, row: i8) {
         for row in (-2..row + 1).rev() {
             for col in 0..10 {
-                if self.board.get(&(col, row - 1)).is_none() {
-                    self.board.remove(&(col, row));
-                } else {
-                    self.board.insert((col, row), self.board[&(col, row - 1)]);
-                }
+                match self.board.get(&(col, row - 1)).copied() {
+                    Some(c) => self.board.insert((col, row), c),
+                    None => self.board.remove(&(col, row)),
+                };
             }
         }
+        if let Some(target) = &mut self.target {
+            target.delete_row(row);
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
     pub fn score(&self) -> u64 {
         self.score
     }
 
-    pub fn add_score(&mut self, action: ScoreAction) {
+    fn add_score(&mut self, action: ScoreAction) {
+        let level = u64::from(self.level);
         self.score += match action {
             ScoreAction::RowCleared(n) => match n {
                 0 => 0,
-                1 => 40,
-                2 => 100,
-                3 => 300,
-                4 => 1200,
-                _ => 1200,
+                1 => 40 * level,
+                2 => 100 * level,
+                3 => 300 * level,
+                4 => 1200 * level,
+                _ => 1200 * level,
             },
             ScoreAction::FallLength(n) => n as u64,
             ScoreAction::HardDrop(n) => n as u64 * 2,
         }
     }
 
-    pub fn upcoming_pixels(&self) -> Option<&[tetronimos::Pixel; 4]> {
-        self.upcoming.as_ref().map(|t| &t.pixels)
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    // tick_interval returns gravity for the current level in miliseconds,
+    // speed curve is borrowed from the tetris guideline
+    pub fn tick_interval(&self) -> u32 {
+        let l = self.level as i32 - 1;
+        let factor = (0.8 - f64::from(l) * 0.007).powi(l);
+        let interval = f64::from(self.settings.gravity) * factor;
+
+        (interval as u32).max(MIN_TICK)
+    }
+
+    pub fn upcoming_pieces(&self) -> impl Iterator<Item = (Type, &[Pixel; 4])> {
+        self.upcoming.iter().map(|t| (t.kind(), &t.pixels))
     }
 
     pub fn is_game_over(&self) -> bool {
         self.game_over
     }
 
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
     pub fn toggle_pause(&mut self) {
+        if self.game_over {
+            return;
+        }
+
         self.pause = !self.pause;
+        if self.pause {
+            self.clock.pause();
+        } else {
+            self.clock.resume();
+        }
     }
 
     pub fn is_paused(&self) -> bool {
         self.pause
     }
 }
+
+// locked cells are stored as a list, as JSON can't have tuples for keys
+mod locked {
+    use super::Cell;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S>(cells: &HashMap<(i8, i8), Cell>, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.collect_seq(cells.iter())
+    }
+
+    pub fn deserialize<'de, D>(d: D) -> Result<HashMap<(i8, i8), Cell>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let cells: Vec<((i8, i8), Cell)> = Vec::deserialize(d)?;
+        Ok(cells.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn left_edge(board: &Board) -> i8 {
+        let t = board.falling.as_ref().unwrap();
+        t.cells().iter().map(|c| c.0).min().unwrap()
+    }
+
+    #[test]
+    fn held_shift_is_counted_once_per_key() {
+        let mut board = Board::new(Settings::new(300));
+        board.update(Duration::ZERO);
+        let start = left_edge(&board);
+
+        board.press(Move::Left);
+        // terminal repeats the key late, the piece catches up
+        board.update(Duration::from_millis(45));
+        board.press(Move::Left);
+
+        assert_eq!(left_edge(&board), start - 3);
+        assert_eq!(board.piece_inputs, 2);
+
+        board.press(Move::HardDrop);
+        assert_eq!(board.stats.kpp(), 3.0);
+    }
+
+    #[test]
+    fn board_with_impossible_goal_is_refused() {
+        let mut board = Board::new(Settings::new(300));
+        board.goal = Some(Goal {
+            lines: 0,
+            tspin: Some(0),
+            perfect_clear: false,
+        });
+
+        assert!(board.check().is_err());
+        // drawing it doesn't panic even if it got around the check
+        board.goal().unwrap().description();
+    }
+
+    #[test]
+    fn cancelled_attack_still_counts() {
+        let mut board = Board::new(Settings::new(300));
+        board.incoming.push(3, Duration::ZERO);
+        // something stays on the board, so it is not a perfect clear
+        board.board.insert((0, 19), Cell::Garbage);
+
+        board.rows_cleared(4);
+        assert_eq!(board.take_attack(), 1);
+        assert_eq!(board.stats.attack(), 4);
+        assert_eq!(board.stats.apm(Duration::from_secs(30)), 8.0);
+    }
+}
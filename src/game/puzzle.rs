@@ -0,0 +1,169 @@
+use super::tetronimos::{Cell, Type};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// Puzzle is a TOML file like this one:
+//
+//   pieces = "TIO"
+//   board = """
+//   XXXXXX..XX
+//   XXXXXXX.XX
+//   """
+//
+//   [goal]
+//   lines = 2
+//   tspin = 2
+//
+// board rows are aligned to the bottom of the field, '.' is an empty
+// cell, piece letters are cells of that piece and anything else is garbage
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Puzzle {
+    #[serde(default)]
+    board: String,
+    pieces: String,
+    goal: Goal,
+}
+
+// Goal is reached once all of its conditions are met
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Goal {
+    // total lines to clear
+    #[serde(default)]
+    pub lines: u32,
+    // a T-spin clearing that many rows, 1 to 3
+    pub tspin: Option<u8>,
+    // a clear leaving the field empty
+    #[serde(default)]
+    pub perfect_clear: bool,
+}
+
+impl Goal {
+    // goal without conditions is to place all the pieces
+    pub fn is_empty(&self) -> bool {
+        self.lines == 0 && self.tspin.is_none() && !self.perfect_clear
+    }
+
+    // check is there for goals read from elsewhere,
+    // there is no T-spin clearing no rows or more than three
+    pub fn check(&self) -> Result<(), String> {
+        match self.tspin {
+            Some(rows) if !(1..=3).contains(&rows) => Err(format!(
+                "wants a T-spin clearing {} rows, it can clear 1 to 3",
+                rows
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let mut parts = Vec::new();
+        if self.lines > 0 {
+            parts.push(format!("clear {} lines", self.lines));
+        }
+        if let Some(rows) = self.tspin {
+            let name = ["single", "double", "triple"];
+            let name = usize::from(rows)
+                .checked_sub(1)
+                .and_then(|i| name.get(i))
+                .unwrap_or(&"triple");
+            parts.push(format!("T-spin {}", name));
+        }
+        if self.perfect_clear {
+            parts.push("perfect clear".to_string());
+        }
+
+        if self.is_empty() {
+            "place all pieces".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+impl Puzzle {
+    pub fn load(path: &Path) -> Result<Puzzle, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Can't read puzzle {}: {}", path.display(), e))?;
+        let puzzle: Puzzle = toml::from_str(&content)
+            .map_err(|e| format!("Puzzle {} is broken: {}", path.display(), e))?;
+
+        puzzle
+            .goal
+            .check()
+            .map_err(|e| format!("Puzzle {} {}", path.display(), e))?;
+        Ok(puzzle)
+    }
+
+    pub fn goal(&self) -> &Goal {
+        &self.goal
+    }
+
+    pub fn cells(&self) -> Result<HashMap<(i8, i8), Cell>, Box<dyn Error>> {
+        let cells = layout(&self.board, "Puzzle board")?
+            .into_iter()
+            .map(|(x, y, c)| {
+                let cell = piece_type(c).map(Cell::Piece).unwrap_or(Cell::Garbage);
+                ((x, y), cell)
+            })
+            .collect();
+        Ok(cells)
+    }
+
+    pub fn pieces(&self) -> Result<Vec<Type>, Box<dyn Error>> {
+        let pieces: Vec<Type> = self
+            .pieces
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| piece_type(c).ok_or_else(|| format!("Puzzle has unknown piece '{}'", c)))
+            .collect::<Result<_, _>>()?;
+
+        if pieces.is_empty() {
+            return Err("Puzzle has no pieces".into());
+        }
+        Ok(pieces)
+    }
+}
+
+// cell of a layout and the char it was drawn with
+type LayoutCell = (i8, i8, char);
+
+// layout reads cells drawn as text rows aligned to the bottom
+// of the field, '.' cells are empty and are left out
+pub fn layout(text: &str, what: &str) -> Result<Vec<LayoutCell>, Box<dyn Error>> {
+    let rows: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    if rows.len() > 20 {
+        return Err(format!("{} is higher than 20 rows", what).into());
+    }
+
+    let mut cells = Vec::new();
+    let top = 20 - rows.len() as i8;
+    for (y, row) in (top..).zip(rows) {
+        if row.chars().count() > 10 {
+            return Err(format!("{} row '{}' is wider than 10 cells", what, row).into());
+        }
+
+        for (x, c) in (0..).zip(row.chars()) {
+            if c != '.' {
+                cells.push((x, y, c));
+            }
+        }
+    }
+
+    Ok(cells)
+}
+
+pub fn piece_type(c: char) -> Option<Type> {
+    Type::ALL
+        .iter()
+        .find(|t| t.name().starts_with(c.to_ascii_uppercase()))
+        .copied()
+}
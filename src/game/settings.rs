@@ -0,0 +1,129 @@
+// game modes and options that can be chosen
+// from the main menu before the game starts
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Mode {
+    Marathon,
+    Sprint,
+    Ultra,
+    Dig,
+    Zen,
+    // two players on the same terminal, not listed in ALL
+    // as it is started from its own menu entry
+    Versus,
+    // predefined board and pieces loaded from a file
+    Puzzle,
+    // opener shape from a setups file shown on the board
+    Trainer,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 5] = [
+        Mode::Marathon,
+        Mode::Sprint,
+        Mode::Ultra,
+        Mode::Dig,
+        Mode::Zen,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Marathon => "Marathon",
+            Mode::Sprint => "Sprint",
+            Mode::Ultra => "Ultra",
+            Mode::Dig => "Dig",
+            Mode::Zen => "Zen",
+            Mode::Versus => "Versus",
+            Mode::Puzzle => "Puzzle",
+            Mode::Trainer => "Trainer",
+        }
+    }
+
+    // placements can be undone only where nothing is at stake
+    pub fn is_practice(&self) -> bool {
+        matches!(self, Mode::Zen | Mode::Puzzle | Mode::Trainer)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Mode::Marathon => "Endless game, level goes up every 10 lines",
+            Mode::Sprint => "Clear 40 lines as fast as possible",
+            Mode::Ultra => "Score as much as possible in 2 minutes",
+            Mode::Dig => "Dig through 10 rows of garbage",
+            Mode::Zen => "No game over, no pressure",
+            Mode::Versus => "Send garbage to your opponent, last one standing wins",
+            Mode::Puzzle => "Reach the goal with the given pieces",
+            Mode::Trainer => "Build the opener shown on the board",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RotationSystem {
+    // super rotation system with wall kicks
+    Srs,
+    // rotation in place, no kicks at all
+    Classic,
+}
+
+impl RotationSystem {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RotationSystem::Srs => "SRS",
+            RotationSystem::Classic => "Classic",
+        }
+    }
+}
+
+pub const MAX_LEVEL: u32 = 15;
+pub const MAX_PREVIEW: usize = 5;
+pub const SPRINT_LINES: u32 = 40;
+pub const ULTRA_SECONDS: u64 = 120;
+pub const DIG_ROWS: i8 = 10;
+pub const MAX_GARBAGE_DELAY: u32 = 3000;
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    pub mode: Mode,
+    pub level: u32,
+    // tick rate in miliseconds at level 1
+    pub gravity: u32,
+    pub preview: usize,
+    pub rotation: RotationSystem,
+    // how long received garbage waits before entering
+    // the board, in miliseconds
+    pub garbage_delay: u32,
+    // random seed is picked for every game if not set
+    pub seed: Option<u64>,
+}
+
+impl Settings {
+    pub fn new(gravity: u32) -> Settings {
+        Settings {
+            mode: Mode::Marathon,
+            level: 1,
+            gravity,
+            preview: 1,
+            rotation: RotationSystem::Srs,
+            garbage_delay: 500,
+            seed: None,
+        }
+    }
+
+    // check tells whether settings read from elsewhere are ones
+    // the menu could have picked, the screen is laid out by them
+    pub fn check(&self) -> Result<(), String> {
+        if self.preview > MAX_PREVIEW {
+            return Err(format!("preview of {} pieces is too long", self.preview));
+        }
+        if !(1..=MAX_LEVEL).contains(&self.level) {
+            return Err(format!("there is no level {}", self.level));
+        }
+        if self.garbage_delay > MAX_GARBAGE_DELAY {
+            return Err(format!("garbage delay {} is too long", self.garbage_delay));
+        }
+        Ok(())
+    }
+}
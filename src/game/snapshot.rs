@@ -0,0 +1,24 @@
+use super::Cell;
+use serde::{Deserialize, Serialize};
+
+// Snapshot is everything the opponent needs to see of a board
+// over the network, falling piece is drawn into the cells
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub cells: Vec<(i8, i8, Cell)>,
+    pub score: u64,
+    pub lines: u32,
+    pub incoming: u32,
+    pub game_over: bool,
+    pub completed: bool,
+}
+
+impl Snapshot {
+    // is_valid tells that all cells are on the field,
+    // snapshots come from the network and can be anything
+    pub fn is_valid(&self) -> bool {
+        self.cells
+            .iter()
+            .all(|&(x, y, _)| (0..10).contains(&x) && (0..20).contains(&y))
+    }
+}
@@ -0,0 +1,140 @@
+use super::tetronimos::Type;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Stats are collected by the board during the game,
+// so that they can be shown live and exported at the end
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pieces: [u32; 7],
+    keys: u32,
+    // amount of singles, doubles, triples and tetrises
+    clears: [u32; 4],
+    // consecutive line clearing pieces, -1 means no combo is going on
+    combo: i32,
+    max_combo: u32,
+    finesse_faults: u32,
+    // T-spins clearing one, two and three rows
+    tspins: [u32; 3],
+    // lines sent by clears, also the ones which only cancelled garbage
+    attack: u32,
+}
+
+impl Stats {
+    pub fn new() -> Stats {
+        Stats {
+            combo: -1,
+            ..Default::default()
+        }
+    }
+
+    pub fn piece_placed(&mut self, t: Type) {
+        self.pieces[t as usize] += 1;
+    }
+
+    pub fn key_pressed(&mut self) {
+        self.keys += 1;
+    }
+
+    pub fn finesse_fault(&mut self, faults: u32) {
+        self.finesse_faults += faults;
+    }
+
+    pub fn rows_cleared(&mut self, amount: u8) {
+        if amount == 0 {
+            self.combo = -1;
+            return;
+        }
+
+        let idx = usize::from(amount.min(4)) - 1;
+        self.clears[idx] += 1;
+
+        self.combo += 1;
+        self.max_combo = self.max_combo.max(self.combo as u32);
+    }
+
+    pub fn attack_sent(&mut self, lines: u32) {
+        self.attack += lines;
+    }
+
+    pub fn tspin_cleared(&mut self, amount: u8) {
+        if amount > 0 {
+            self.tspins[usize::from(amount.min(3)) - 1] += 1;
+        }
+    }
+
+    pub fn tspins(&self, amount: u8) -> u32 {
+        match amount {
+            1..=3 => self.tspins[usize::from(amount) - 1],
+            _ => 0,
+        }
+    }
+
+    pub fn pieces(&self, t: Type) -> u32 {
+        self.pieces[t as usize]
+    }
+
+    pub fn total_pieces(&self) -> u32 {
+        self.pieces.iter().sum()
+    }
+
+    pub fn singles(&self) -> u32 {
+        self.clears[0]
+    }
+
+    pub fn doubles(&self) -> u32 {
+        self.clears[1]
+    }
+
+    pub fn triples(&self) -> u32 {
+        self.clears[2]
+    }
+
+    pub fn tetrises(&self) -> u32 {
+        self.clears[3]
+    }
+
+    pub fn combo(&self) -> i32 {
+        self.combo
+    }
+
+    pub fn max_combo(&self) -> u32 {
+        self.max_combo
+    }
+
+    pub fn finesse_faults(&self) -> u32 {
+        self.finesse_faults
+    }
+
+    pub fn attack(&self) -> u32 {
+        self.attack
+    }
+
+    // pieces per second
+    pub fn pps(&self, elapsed: Duration) -> f64 {
+        let secs = elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+
+        f64::from(self.total_pieces()) / secs
+    }
+
+    // attack lines per minute
+    pub fn apm(&self, elapsed: Duration) -> f64 {
+        let mins = elapsed.as_secs_f64() / 60.0;
+        if mins == 0.0 {
+            return 0.0;
+        }
+
+        f64::from(self.attack) / mins
+    }
+
+    // keys per piece
+    pub fn kpp(&self) -> f64 {
+        match self.total_pieces() {
+            0 => 0.0,
+            n => f64::from(self.keys) / f64::from(n),
+        }
+    }
+}
@@ -1,18 +1,22 @@
-use rand::{thread_rng, Rng};
-use std::collections::HashMap;
-use tui::style::Color;
+use super::settings::RotationSystem;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 
 mod tables;
 
-#[derive(Copy, Clone)]
+// Cells is a comparable form of tetronimo position,
+// coordinates of its pixels sorted
+pub type Cells = [(i8, i8); 4];
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Pixel {
     pub x: i8,
     pub y: i8,
-
-    pub c: Color,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     I,
     L,
@@ -23,21 +27,79 @@ pub enum Type {
     Z,
 }
 
+impl Type {
+    pub const ALL: [Type; 7] = [
+        Type::I,
+        Type::L,
+        Type::T,
+        Type::S,
+        Type::O,
+        Type::J,
+        Type::Z,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Type::I => "I",
+            Type::L => "L",
+            Type::T => "T",
+            Type::S => "S",
+            Type::O => "O",
+            Type::J => "J",
+            Type::Z => "Z",
+        }
+    }
+}
+
+// Cell is what a locked cell of the board is made of,
+// how it looks is up to the one drawing it
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Cell {
+    Piece(Type),
+    Garbage,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TetronimoBag {
     types: [Type; 7],
     size: u8,
+    rng: ChaCha12Rng,
+    // fixed sequence of pieces used instead of the random ones
+    script: Option<VecDeque<Type>>,
 }
 
 impl TetronimoBag {
-    pub fn new() -> TetronimoBag {
+    // bags with the same seed give the same sequence of pieces
+    pub fn new(seed: u64) -> TetronimoBag {
         TetronimoBag {
             types: TetronimoBag::fill_bag(),
             size: 7,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            script: None,
+        }
+    }
+
+    // scripted bag gives exactly the pieces it was made with
+    pub fn scripted(types: Vec<Type>) -> TetronimoBag {
+        TetronimoBag {
+            script: Some(types.into()),
+            ..TetronimoBag::new(0)
         }
     }
 
-    pub fn draw_next(&mut self) -> Type {
-        let index = thread_rng().gen_range(0..self.size);
+    // is_empty tells that scripted pieces ran out,
+    // random bag never does
+    pub fn is_empty(&self) -> bool {
+        self.script.as_ref().is_some_and(|s| s.is_empty())
+    }
+
+    // draw_next returns None once scripted pieces run out
+    pub fn draw_next(&mut self) -> Option<Type> {
+        if let Some(script) = &mut self.script {
+            return script.pop_front();
+        }
+
+        let index = self.rng.gen_range(0..self.size);
         let result = self.types[index as usize];
         // move it all around
         for i in index..self.size - 1 {
@@ -48,22 +110,15 @@ impl TetronimoBag {
             self.types = TetronimoBag::fill_bag();
             self.size = 7;
         }
-        result
+        Some(result)
     }
 
     fn fill_bag() -> [Type; 7] {
-        [
-            Type::I,
-            Type::L,
-            Type::T,
-            Type::S,
-            Type::O,
-            Type::J,
-            Type::Z,
-        ]
+        Type::ALL
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Tetronimo {
     pub pixels: [Pixel; 4],
 
@@ -78,7 +133,7 @@ impl Tetronimo {
         Tetronimo {
             pixels: tables::fill_new_pixels(t),
             dropped: 0,
-            t: t,
+            t,
             rotation: 0,
         }
     }
@@ -91,21 +146,38 @@ impl Tetronimo {
         self.dropped
     }
 
+    pub fn kind(&self) -> Type {
+        self.t
+    }
+
+    pub fn rotation(&self) -> i8 {
+        self.rotation
+    }
+
+    pub fn cells(&self) -> Cells {
+        let mut res = [(0, 0); 4];
+        for (c, p) in res.iter_mut().zip(&self.pixels) {
+            *c = (p.x, p.y);
+        }
+        res.sort_unstable();
+        res
+    }
+
     // move_tick returns false in case we cannot move tetronimo anymore
-    pub fn check_pixel(&self, x: i8, y: i8) -> Option<Color> {
+    pub fn check_pixel(&self, x: i8, y: i8) -> Option<Type> {
         for p in &self.pixels {
             if p.x == x && p.y == y {
-                return Some(p.c);
+                return Some(self.t);
             }
         }
 
         None
     }
 
-    pub fn move_offset(&mut self, offset: (i8, i8), board: &HashMap<(i8, i8), Color>) -> bool {
+    pub fn move_offset(&mut self, offset: (i8, i8), board: &HashMap<(i8, i8), Cell>) -> bool {
         for p in &mut self.pixels {
             let (new_x, new_y) = (p.x + offset.0, p.y + offset.1);
-            if new_y == 20 || new_x == 10 || new_x == -1 || board.get(&(new_x, new_y)).is_some() {
+            if new_y == 20 || new_x == 10 || new_x == -1 || board.contains_key(&(new_x, new_y)) {
                 return false;
             }
         }
@@ -143,18 +215,29 @@ impl Tetronimo {
         }
     }
 
-    pub fn rotate_and_kick(&mut self, clockwise: bool, board: &HashMap<(i8, i8), Color>) {
+    pub fn rotate_and_kick(
+        &mut self,
+        clockwise: bool,
+        board: &HashMap<(i8, i8), Cell>,
+        rs: RotationSystem,
+    ) {
         let previous_rotation = self.rotation;
         self.rotate(clockwise);
 
-        let offsets = tables::get_kick_offsets(previous_rotation, self.rotation, self.t);
+        let mut offsets = tables::get_kick_offsets(previous_rotation, self.rotation, self.t);
+        if rs == RotationSystem::Classic {
+            // first offset is not a kick, it is what makes
+            // I and O pieces rotate around their true center
+            offsets.truncate(1);
+        }
+
         for o in offsets {
             let mut collides = false;
             for p in self.pixels {
                 let new_x = p.x + o.0;
                 let new_y = p.y - o.1;
 
-                if new_x < 0 || new_x > 9 || new_y > 19 || board.get(&(new_x, new_y)).is_some() {
+                if !(0..=9).contains(&new_x) || new_y > 19 || board.contains_key(&(new_x, new_y)) {
                     collides = true;
                     break;
                 }
@@ -1,15 +1,9 @@
 use super::{Pixel, Type};
 
-use tui::style::Color;
-
 // we use tables for tetronimos spawn
 // as the easiest way
 pub fn fill_new_pixels(t: Type) -> [Pixel; 4] {
-    let res = [Pixel {
-        x: 0,
-        y: 0,
-        c: Color::White,
-    }; 4];
+    let res = [Pixel { x: 0, y: 0 }; 4];
     match t {
         Type::I => fill_new_i(res),
         Type::L => fill_new_l(res),
@@ -22,78 +16,64 @@ pub fn fill_new_pixels(t: Type) -> [Pixel; 4] {
 }
 
 fn fill_new_i(mut ps: [Pixel; 4]) -> [Pixel; 4] {
-    let c = Color::Cyan;
-
-    ps[0] = Pixel { x: 4, y: -1, c: c };
-    ps[1] = Pixel { x: 3, y: -1, c: c };
-    ps[2] = Pixel { x: 5, y: -1, c: c };
-    ps[3] = Pixel { x: 6, y: -1, c: c };
+    ps[0] = Pixel { x: 4, y: -1 };
+    ps[1] = Pixel { x: 3, y: -1 };
+    ps[2] = Pixel { x: 5, y: -1 };
+    ps[3] = Pixel { x: 6, y: -1 };
 
     ps
 }
 
 fn fill_new_l(mut ps: [Pixel; 4]) -> [Pixel; 4] {
-    let c = Color::Yellow;
-
-    ps[0] = Pixel { x: 4, y: -1, c: c };
-    ps[1] = Pixel { x: 3, y: -1, c: c };
-    ps[2] = Pixel { x: 5, y: -1, c: c };
-    ps[3] = Pixel { x: 5, y: -2, c: c };
+    ps[0] = Pixel { x: 4, y: -1 };
+    ps[1] = Pixel { x: 3, y: -1 };
+    ps[2] = Pixel { x: 5, y: -1 };
+    ps[3] = Pixel { x: 5, y: -2 };
 
     ps
 }
 
 fn fill_new_t(mut ps: [Pixel; 4]) -> [Pixel; 4] {
-    let c = Color::Magenta;
-
-    ps[0] = Pixel { x: 4, y: -1, c: c };
-    ps[1] = Pixel { x: 4, y: -2, c: c };
-    ps[2] = Pixel { x: 3, y: -1, c: c };
-    ps[3] = Pixel { x: 5, y: -1, c: c };
+    ps[0] = Pixel { x: 4, y: -1 };
+    ps[1] = Pixel { x: 4, y: -2 };
+    ps[2] = Pixel { x: 3, y: -1 };
+    ps[3] = Pixel { x: 5, y: -1 };
 
     ps
 }
 
 fn fill_new_s(mut ps: [Pixel; 4]) -> [Pixel; 4] {
-    let c = Color::Green;
-
-    ps[0] = Pixel { x: 4, y: -1, c: c };
-    ps[1] = Pixel { x: 3, y: -1, c: c };
-    ps[2] = Pixel { x: 4, y: -2, c: c };
-    ps[3] = Pixel { x: 5, y: -2, c: c };
+    ps[0] = Pixel { x: 4, y: -1 };
+    ps[1] = Pixel { x: 3, y: -1 };
+    ps[2] = Pixel { x: 4, y: -2 };
+    ps[3] = Pixel { x: 5, y: -2 };
 
     ps
 }
 
 fn fill_new_o(mut ps: [Pixel; 4]) -> [Pixel; 4] {
-    let c = Color::LightYellow;
-
-    ps[0] = Pixel { x: 4, y: -1, c: c };
-    ps[1] = Pixel { x: 4, y: -2, c: c };
-    ps[2] = Pixel { x: 5, y: -2, c: c };
-    ps[3] = Pixel { x: 5, y: -1, c: c };
+    ps[0] = Pixel { x: 4, y: -1 };
+    ps[1] = Pixel { x: 4, y: -2 };
+    ps[2] = Pixel { x: 5, y: -2 };
+    ps[3] = Pixel { x: 5, y: -1 };
 
     ps
 }
 
 fn fill_new_j(mut ps: [Pixel; 4]) -> [Pixel; 4] {
-    let c = Color::LightBlue;
-
-    ps[0] = Pixel { x: 4, y: -1, c: c };
-    ps[1] = Pixel { x: 3, y: -1, c: c };
-    ps[2] = Pixel { x: 3, y: -2, c: c };
-    ps[3] = Pixel { x: 5, y: -1, c: c };
+    ps[0] = Pixel { x: 4, y: -1 };
+    ps[1] = Pixel { x: 3, y: -1 };
+    ps[2] = Pixel { x: 3, y: -2 };
+    ps[3] = Pixel { x: 5, y: -1 };
 
     ps
 }
 
 fn fill_new_z(mut ps: [Pixel; 4]) -> [Pixel; 4] {
-    let c = Color::Red;
-
-    ps[0] = Pixel { x: 4, y: -1, c: c };
-    ps[1] = Pixel { x: 3, y: -2, c: c };
-    ps[2] = Pixel { x: 4, y: -2, c: c };
-    ps[3] = Pixel { x: 5, y: -1, c: c };
+    ps[0] = Pixel { x: 4, y: -1 };
+    ps[1] = Pixel { x: 3, y: -2 };
+    ps[2] = Pixel { x: 4, y: -2 };
+    ps[3] = Pixel { x: 5, y: -1 };
 
     ps
 }
@@ -127,6 +107,7 @@ fn get_kicks_o(from: i8, to: i8) -> Vec<(i8, i8)> {
     vec![(o_f.0 - o_t.0, o_f.1 - o_t.1)]
 }
 
+#[allow(clippy::needless_range_loop)]
 fn get_kicks_i(from: i8, to: i8) -> Vec<(i8, i8)> {
     let mut res = Vec::new();
     for i in 0..5 {
@@ -138,6 +119,7 @@ fn get_kicks_i(from: i8, to: i8) -> Vec<(i8, i8)> {
     res
 }
 
+#[allow(clippy::needless_range_loop)]
 fn get_kicks_common(from: i8, to: i8) -> Vec<(i8, i8)> {
     let mut res = Vec::new();
     for i in 0..5 {
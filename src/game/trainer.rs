@@ -0,0 +1,225 @@
+use super::puzzle::{layout, piece_type};
+use super::tetronimos::{Cell, Pixel, Type};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// Setups is a TOML file with any amount of setups like this one:
+//
+//   [[setup]]
+//   name = "Stack"
+//   shape = """
+//   ..L.......
+//   LLL.......
+//   """
+//
+// shape rows are aligned to the bottom of the field the same
+// way puzzle boards are, letters tell which piece goes there
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Setups {
+    setup: Vec<Setup>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Setup {
+    name: String,
+    shape: String,
+}
+
+impl Setup {
+    // load finds the setup by its name in the file,
+    // the first one is taken when no name is given
+    pub fn load(path: &Path, name: Option<&str>) -> Result<Setup, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Can't read setups {}: {}", path.display(), e))?;
+        let setups: Setups = toml::from_str(&content)
+            .map_err(|e| format!("Setups {} are broken: {}", path.display(), e))?;
+
+        let mut setups = setups.setup.into_iter();
+        let setup = match name {
+            Some(name) => setups.find(|s| s.name.eq_ignore_ascii_case(name)),
+            None => setups.next(),
+        };
+        setup.ok_or_else(|| {
+            let name = name.unwrap_or_default();
+            format!("There is no setup '{}' in {}", name, path.display()).into()
+        })
+    }
+
+    pub fn target(&self) -> Result<Target, Box<dyn Error>> {
+        let what = format!("Setup '{}'", self.name);
+        let cells = layout(&self.shape, &what)?
+            .into_iter()
+            .map(|(x, y, c)| match piece_type(c) {
+                Some(t) => Ok((x, y, t)),
+                None => Err(format!("{} has unknown piece '{}'", what, c)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if cells.is_empty() {
+            return Err(format!("{} is empty", what).into());
+        }
+        Ok(Target {
+            name: self.name.clone(),
+            cells,
+            mistakes: Vec::new(),
+        })
+    }
+}
+
+// Target is the shape player is building, it is kept in the
+// board coordinates and moves down together with cleared rows
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Target {
+    pub name: String,
+    cells: Vec<(i8, i8, Type)>,
+    // locked cells that are not where the shape wants them
+    mistakes: Vec<(i8, i8)>,
+}
+
+impl Target {
+    pub fn get(&self, x: i8, y: i8) -> Option<Type> {
+        self.cells
+            .iter()
+            .find(|(cx, cy, _)| *cx == x && *cy == y)
+            .map(|(_, _, t)| *t)
+    }
+
+    pub fn is_mistake(&self, x: i8, y: i8) -> bool {
+        self.mistakes.contains(&(x, y))
+    }
+
+    pub fn mistakes(&self) -> usize {
+        self.mistakes.len()
+    }
+
+    // check marks every cell of the locked piece which the shape doesn't
+    // have for this piece. There is no hold, so pieces the shape has no
+    // room left for are put out of the way, only covering the shape counts.
+    pub fn check(&mut self, kind: Type, pixels: &[Pixel; 4], locked: &HashMap<(i8, i8), Cell>) {
+        let wanted = self
+            .cells
+            .iter()
+            .any(|(x, y, t)| *t == kind && !locked.contains_key(&(*x, *y)));
+
+        for p in pixels {
+            let mistake = match self.get(p.x, p.y) {
+                Some(t) => !wanted || t != kind,
+                None => wanted,
+            };
+            if mistake {
+                self.mistakes.push((p.x, p.y));
+            }
+        }
+    }
+
+    // is_built tells whether every cell of the shape is taken
+    pub fn is_built(&self, locked: &HashMap<(i8, i8), Cell>) -> bool {
+        self.cells
+            .iter()
+            .all(|(x, y, _)| locked.contains_key(&(*x, *y)))
+    }
+
+    // delete_row follows the board, cells of the row are gone
+    // and everything above it falls one row down
+    pub fn delete_row(&mut self, row: i8) {
+        self.cells.retain(|(_, y, _)| *y != row);
+        self.mistakes.retain(|(_, y)| *y != row);
+
+        for (_, y, _) in &mut self.cells {
+            if *y < row {
+                *y += 1;
+            }
+        }
+        for (_, y) in &mut self.mistakes {
+            if *y < row {
+                *y += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(shape: &str) -> Target {
+        let setup = Setup {
+            name: "Test".to_string(),
+            shape: shape.to_string(),
+        };
+        setup.target().unwrap()
+    }
+
+    fn pixels(cells: [(i8, i8); 4]) -> [Pixel; 4] {
+        cells.map(|(x, y)| Pixel { x, y })
+    }
+
+    #[test]
+    fn piece_in_its_place_is_no_mistake() {
+        let mut target = target("IIII......");
+        target.check(
+            Type::I,
+            &pixels([(0, 19), (1, 19), (2, 19), (3, 19)]),
+            &HashMap::new(),
+        );
+
+        assert_eq!(target.mistakes(), 0);
+    }
+
+    #[test]
+    fn wanted_piece_out_of_its_place_is_a_mistake() {
+        let mut target = target("IIII......");
+        target.check(
+            Type::I,
+            &pixels([(6, 19), (7, 19), (8, 19), (9, 19)]),
+            &HashMap::new(),
+        );
+
+        assert_eq!(target.mistakes(), 4);
+    }
+
+    #[test]
+    fn unused_piece_may_go_off_the_shape() {
+        let mut target = target("IIII......");
+        target.check(
+            Type::O,
+            &pixels([(8, 18), (9, 18), (8, 19), (9, 19)]),
+            &HashMap::new(),
+        );
+
+        assert_eq!(target.mistakes(), 0);
+    }
+
+    #[test]
+    fn unused_piece_over_the_shape_is_a_mistake() {
+        let mut target = target("IIII......");
+        target.check(
+            Type::O,
+            &pixels([(3, 18), (4, 18), (3, 19), (4, 19)]),
+            &HashMap::new(),
+        );
+
+        assert_eq!(target.mistakes(), 1);
+    }
+
+    #[test]
+    fn piece_is_unused_once_its_cells_are_taken() {
+        let mut target = target("IIII......");
+        let locked = [(0, 19), (1, 19), (2, 19), (3, 19)]
+            .iter()
+            .map(|&c| (c, Cell::Piece(Type::I)))
+            .collect();
+        target.check(
+            Type::I,
+            &pixels([(6, 18), (7, 18), (8, 18), (9, 18)]),
+            &locked,
+        );
+
+        assert_eq!(target.mistakes(), 0);
+    }
+}
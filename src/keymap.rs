@@ -0,0 +1,54 @@
+use crate::game::Move;
+use termion::event::Key;
+
+// Keymap tells which keys control the falling piece,
+// several keys can be bound to the same move
+pub struct Keymap {
+    bindings: &'static [(Key, Move)],
+}
+
+pub const SOLO: Keymap = Keymap {
+    bindings: &[
+        (Key::Left, Move::Left),
+        (Key::Right, Move::Right),
+        (Key::Down, Move::Down),
+        (Key::Up, Move::RotateCw),
+        (Key::Char(' '), Move::RotateCw),
+        (Key::Char('x'), Move::RotateCw),
+        (Key::Char('z'), Move::RotateCcw),
+        (Key::Char('\n'), Move::HardDrop),
+    ],
+};
+
+// in versus the left player gets WASD side of the keyboard
+pub const PLAYER_ONE: Keymap = Keymap {
+    bindings: &[
+        (Key::Char('a'), Move::Left),
+        (Key::Char('d'), Move::Right),
+        (Key::Char('s'), Move::Down),
+        (Key::Char('w'), Move::RotateCw),
+        (Key::Char('e'), Move::RotateCcw),
+        (Key::Char(' '), Move::HardDrop),
+    ],
+};
+
+// and the right player gets the arrows
+pub const PLAYER_TWO: Keymap = Keymap {
+    bindings: &[
+        (Key::Left, Move::Left),
+        (Key::Right, Move::Right),
+        (Key::Down, Move::Down),
+        (Key::Up, Move::RotateCw),
+        (Key::Char('/'), Move::RotateCcw),
+        (Key::Char('\n'), Move::HardDrop),
+    ],
+};
+
+impl Keymap {
+    pub fn action(&self, key: Key) -> Option<Move> {
+        self.bindings
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, m)| *m)
+    }
+}
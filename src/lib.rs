@@ -1,100 +1,771 @@
-use events::{Event, Events};
-use game::{Board, MoveDirection, ScoreAction};
+use config::Config;
+use events::{Event, Events, FocusReports, FRAME};
+use game::ai::Bot;
+use game::settings::{self, Mode, Settings};
+use game::{Board, Move, Puzzle, Setup};
+use menu::{Action, Menu};
+use online::Online;
+use pause::PauseMenu;
+use scores::{Entry, HighScores};
+use signals::Signal;
 use std::error::Error;
-use std::io;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use termion::event::Key;
-use termion::raw::IntoRawMode;
-use termion::screen::AlternateScreen;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{AlternateScreen, ToAlternateScreen, ToMainScreen};
+use theme::{CellStyle, Theme};
 use tui::backend::{Backend, TermionBackend};
 use tui::Terminal;
+use versus::Versus;
 
+mod config;
+pub mod crash;
 mod events;
+mod export;
 pub mod flags;
 mod game;
+mod keymap;
+mod menu;
+mod net;
+mod online;
+mod pause;
+mod save;
+mod scores;
+mod signals;
+mod spectate;
+mod theme;
 mod ui;
+mod versus;
+
+// how long game over screen is shown before autoplay starts a new game
+const AUTOPLAY_RESTART: Duration = Duration::from_secs(3);
+// spectators redraw this often, published frames come as they change
+const WATCH_TICK: Duration = Duration::from_millis(50);
+// nothing moves in the menu or in a stopped game,
+// loop wakes up only to notice resizes
+const IDLE_TICK: Duration = Duration::from_millis(100);
+const FUMEN_URL: &str = "https://fumen.zui.jp/?";
+
+type Screen = Terminal<TermionBackend<AlternateScreen<io::Stdout>>>;
 
 pub fn run(f: flags::Flags) -> Result<(), Box<dyn Error>> {
-    let stdout = io::stdout().into_raw_mode()?;
-    let stdout = AlternateScreen::from(stdout);
+    let config = Config::load()?;
+    let theme = Theme::find(
+        f.theme.as_deref().or(config.theme.as_deref()),
+        &config.themes,
+        f.monochrome || config.monochrome,
+    )?
+    .with_cells(
+        f.cells
+            .as_deref()
+            .and_then(CellStyle::from_name)
+            .unwrap_or(config.cells),
+    );
+
+    let mut settings = Settings::new(f.tick);
+    settings.seed = f.seed;
+
+    // network game is set up before the terminal is taken over,
+    // so that waiting and connection errors are printed as usual
+    let online = if let Some(port) = f.host {
+        println!("Waiting for an opponent on port {}...", port);
+        Some(Online::host(port, settings)?)
+    } else if let Some(addr) = &f.join {
+        Some(Online::join(addr, settings)?)
+    } else {
+        None
+    };
+
+    // saved game or fumen setup is loaded before the terminal
+    // is taken over, so that player can see why it couldn't be
+    let loaded = if f.resume {
+        Some(save::load()?)
+    } else if let Some(code) = &f.fumen {
+        Some(Board::from_fumen(settings, code)?)
+    } else if let Some(path) = &f.puzzle {
+        // the whole sequence is shown, puzzles are about planning ahead
+        let settings = Settings {
+            preview: settings::MAX_PREVIEW,
+            ..settings
+        };
+        Some(Board::from_puzzle(settings, &Puzzle::load(path)?)?)
+    } else if let Some(path) = &f.trainer {
+        let setup = Setup::load(path, f.setup.as_deref())?;
+        Some(Board::from_setup(settings, &setup)?)
+    } else {
+        None
+    };
+
+    let watched = match &f.watch {
+        Some(addr) => Some(spectate::watch(addr)?),
+        None => None,
+    };
+    let publisher = match &f.publish {
+        Some(addr) => Some(spectate::Publisher::bind(addr)?),
+        None => None,
+    };
+
+    // raw mode is kept apart from the screen, so that
+    // the terminal can be given back while the game is suspended
+    let raw = io::stdout().into_raw_mode()?;
+    let stdout = AlternateScreen::from(io::stdout());
     let backend = TermionBackend::new(stdout);
 
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let events = Events::new(f.tick);
+    let mut events = Events::new(f.autoplay)?;
 
-    let mut board = Board::new();
+    if let Some(boards) = watched {
+        events.set_tick(WATCH_TICK);
+        return watch(&mut terminal, &raw, &mut events, boards, &theme);
+    }
+    let mut focus = FocusReports::enable()?;
 
-    loop {
-        terminal.draw(|f| ui::draw(f, &board))?;
+    let mut menu = Menu::new(settings);
+    let mut scores = HighScores::load();
+    let mut game: Option<Game> = None;
+    let mut recorded = false;
+    let mut pause = PauseMenu::new();
+
+    // with autoplay bot starts playing right away,
+    // without going through the menu
+    let mut bot = None;
+    let mut over_at = None;
+    if f.autoplay.is_some() {
+        bot = Some(Bot::new());
+        game = Some(Game::Solo(Box::new(Board::new(settings))));
+    }
+    // loaded setups are restarted from where they were loaded,
+    // saved games are not setups
+    let mut start = loaded.as_ref().filter(|_| !f.resume).cloned();
+    if let Some(board) = loaded {
+        game = Some(Game::Solo(Box::new(board)));
+    }
+    // exported fumen codes are printed once the terminal is back to normal
+    let mut fumens = Vec::new();
+    if let Some(o) = online {
+        game = Some(Game::Online(Box::new(o)));
+    }
+
+    // drawing is skipped while nothing on the screen changes,
+    // every redraw is a burst of output on slow connections
+    let mut dirty = true;
+    let mut size = terminal.size()?;
+    let mut failed = None;
+    // a panic in the game is caught while the game is still around,
+    // so that it can be written down before the terminal is restored
+    let played = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), Box<dyn Error>> {
+        loop {
+            events.set_tick(match &game {
+                Some(g) if !g.is_still() || bot.is_some() => FRAME,
+                _ => IDLE_TICK,
+            });
+
+            let resized = terminal.size()? != size;
+            if dirty || resized {
+                terminal.draw(|f| {
+                    match &game {
+                        Some(Game::Solo(board)) => ui::draw(f, board, &theme),
+                        Some(Game::Versus(v)) => {
+                            ui::versus::draw(f, [&v.boards[0], &v.boards[1]], &theme)
+                        }
+                        Some(Game::Online(o)) => {
+                            ui::online::draw(f, &o.board, o.opponent.as_ref(), o.status, &theme)
+                        }
+                        None => ui::menu::draw(f, &menu, &scores, &theme),
+                    }
+
+                    // pause menu goes over the hidden board
+                    if let Some(g) = game.as_ref().filter(|g| g.is_paused()) {
+                        ui::pause::draw(f, &pause, g.boards()[0].settings(), &theme);
+                    }
+                })?;
+                size = terminal.size()?;
+            }
+
+            // spectators get the same frames as the screen, the ones
+            // who just came get the current frame even if it is still
+            if let Some(p) = &publisher {
+                if dirty || resized || p.has_newcomers() {
+                    p.publish(&game.as_ref().map(Game::boards).unwrap_or_default());
+                }
+            }
 
-        match events.get_event()? {
-            Event::Input(key) => match key {
-                Key::Char('q') | Key::Ctrl('c') => {
-                    break;
+            let event = events.next();
+            // frames change nothing in the menu or in a stopped game,
+            // unless the bot is about to restart it
+            dirty = match (&event, &game) {
+                (Event::Frame(_), Some(g)) => !g.is_still() || bot.is_some(),
+                (Event::Frame(_), None) => false,
+                _ => true,
+            };
+            if let Event::Input(Key::Ctrl('c'))
+            | Event::Signal(Signal::Terminate)
+            | Event::Error(_) = event
+            {
+                if let Some(g) = game.as_mut() {
+                    if !recorded {
+                        leave_game(g, &mut scores, f.export.as_deref(), bot.is_some())?;
+                    }
                 }
-                Key::Right => {
-                    board.move_tetrinomo(MoveDirection::Right);
+                if let Event::Error(e) = event {
+                    failed = Some(e);
                 }
-                Key::Left => {
-                    board.move_tetrinomo(MoveDirection::Left);
+                break;
+            }
+
+            match event {
+                Event::Input(Key::Ctrl('z')) | Event::Signal(Signal::Suspend) => {
+                    // game doesn't go on while nobody can see it
+                    if let Some(g) = game.as_mut().filter(|_| bot.is_none()) {
+                        g.pause();
+                    }
+                    drop(focus);
+                    suspend(&mut terminal, &raw)?;
+                    focus = FocusReports::enable()?;
+                    continue;
                 }
-                Key::Down => {
-                    board.move_tetrinomo(MoveDirection::Down);
+                // game may have been stopped without asking it first
+                Event::Signal(Signal::Continue) => {
+                    resume(&mut terminal, &raw)?;
+                    continue;
                 }
-                Key::Char(' ') | Key::Char('x') | Key::Up => {
-                    board.rotate(true);
+                Event::Signal(Signal::Resize) => continue,
+                _ => {}
+            }
+
+            let g = match game.as_mut() {
+                Some(g) => g,
+                None => {
+                    if let Event::Input(key) = event {
+                        let started = match menu.handle_key(key) {
+                            Action::Start(settings) => Game::Solo(Box::new(Board::new(settings))),
+                            Action::Versus(settings) => {
+                                Game::Versus(Box::new(Versus::new(settings)))
+                            }
+                            Action::Quit => break,
+                            Action::None => continue,
+                        };
+
+                        game = Some(started);
+                        recorded = false;
+                        bot = None;
+                        start = None;
+                    }
+                    continue;
                 }
-                Key::Char('\n') => {
-                    board.hard_drop();
+            };
 
-                    // we should check whether we can delete rows
-                    // after hard drop, too
-                    check_rows(&mut terminal, &mut board)?;
+            // player is not looking at the game any more
+            if let Event::Focus(false) = event {
+                if bot.is_none() {
+                    g.pause();
                 }
-                Key::Esc => board.toggle_pause(),
-                Key::Char('z') => {
-                    board.rotate(false);
+                continue;
+            }
+
+            // keys go to the pause menu while the game is paused
+            if let (Event::Input(key), true) = (&event, g.is_paused()) {
+                let keep_playing = match pause.handle_key(*key) {
+                    pause::Action::None => true,
+                    pause::Action::Resume => {
+                        g.toggle_pause();
+                        true
+                    }
+                    pause::Action::Restart => {
+                        match g.restart(start.as_ref(), settings.seed) {
+                            Some(restarted) => {
+                                if !recorded {
+                                    finish_game(
+                                        g,
+                                        &mut scores,
+                                        f.export.as_deref(),
+                                        bot.is_some(),
+                                    )?;
+                                }
+                                *g = restarted;
+                                recorded = false;
+                            }
+                            None => g.toggle_pause(),
+                        }
+                        true
+                    }
+                    pause::Action::Quit => false,
+                };
+
+                if !keep_playing {
+                    if !recorded {
+                        leave_game(g, &mut scores, f.export.as_deref(), bot.is_some())?;
+                    }
+                    game = None;
                 }
-                _ => {
-                    // nothing to do here
+                continue;
+            }
+
+            let keep_playing = match g {
+                Game::Solo(board) => {
+                    if bot.is_some() && board.is_game_over() {
+                        let at = *over_at.get_or_insert_with(Instant::now);
+                        if at.elapsed() >= AUTOPLAY_RESTART {
+                            **board = Board::new(*board.settings());
+                            recorded = false;
+                            over_at = None;
+                        }
+                    }
+
+                    handle_game_event(
+                        &mut terminal,
+                        &theme,
+                        board,
+                        bot.as_mut(),
+                        &mut fumens,
+                        event,
+                    )?
                 }
-            },
-            Event::Tick => {
-                // if tetronimo fell to the end
-                if board.tick() {
-                    check_rows(&mut terminal, &mut board)?;
+                Game::Versus(v) => handle_versus_event(&mut terminal, &theme, v, event)?,
+                Game::Online(o) => handle_online_event(&mut terminal, &theme, o, event)?,
+            };
+
+            if !keep_playing {
+                if !recorded {
+                    leave_game(g, &mut scores, f.export.as_deref(), bot.is_some())?;
                 }
-                // process tick here
+
+                // back to the main menu
+                game = None;
+                continue;
+            }
+
+            if g.is_over() && !recorded {
+                recorded = true;
+                finish_game(g, &mut scores, f.export.as_deref(), bot.is_some())?;
+            }
+            // undo can bring a finished puzzle or trainer game back,
+            // the way it ends then is recorded again
+            if recorded && !g.is_over() {
+                recorded = false;
+            }
+        }
+        Ok(())
+    }));
+
+    let crashed = match played {
+        Ok(played) => {
+            played?;
+            None
+        }
+        Err(panic) => {
+            if let (Some(path), Some(g)) = (&f.crash_dump, &game) {
+                crash::dump(path, &g.boards());
             }
+            Some(panic)
         }
+    };
+
+    events.shutdown();
+    drop(terminal);
+    drop(raw);
+    // leaving the alternate screen may still wait in the buffer,
+    // the error would be printed on it and lost then
+    io::stdout().flush()?;
+    // panic goes on to be reported once the terminal is restored
+    if let Some(panic) = crashed {
+        panic::resume_unwind(panic);
+    }
+    for code in fumens {
+        println!("{}{}", FUMEN_URL, code);
     }
 
-    Ok(())
+    match failed {
+        Some(e) => Err(format!("Can't read the terminal: {}", e).into()),
+        None => Ok(()),
+    }
+}
+
+// watch shows the published game until the stream ends or spectator leaves
+fn watch(
+    terminal: &mut Screen,
+    raw: &RawTerminal<io::Stdout>,
+    events: &mut Events,
+    frames: mpsc::Receiver<Vec<Board>>,
+    theme: &Theme,
+) -> Result<(), Box<dyn Error>> {
+    let mut boards = Vec::new();
+
+    loop {
+        loop {
+            match frames.try_recv() {
+                Ok(b) => boards = b,
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+
+        terminal.draw(|f| match boards.as_slice() {
+            [board] => ui::draw(f, board, theme),
+            [left, right] => ui::versus::draw(f, [left, right], theme),
+            _ => ui::draw_message(f, "Waiting for the game to start...", theme),
+        })?;
+
+        match events.next() {
+            Event::Input(Key::Char('q') | Key::Ctrl('c')) => return Ok(()),
+            Event::Signal(Signal::Terminate) => return Ok(()),
+            Event::Input(Key::Ctrl('z')) | Event::Signal(Signal::Suspend) => {
+                suspend(terminal, raw)?
+            }
+            Event::Signal(Signal::Continue) => resume(terminal, raw)?,
+            Event::Error(e) => return Err(format!("Can't read the terminal: {}", e).into()),
+            _ => {}
+        }
+    }
+}
+
+// suspend gives the terminal back to the shell and stops the game,
+// the terminal is taken over again once the game is continued
+fn suspend(terminal: &mut Screen, raw: &RawTerminal<io::Stdout>) -> io::Result<()> {
+    terminal.show_cursor()?;
+    write!(terminal.backend_mut(), "{}", ToMainScreen)?;
+    Write::flush(terminal.backend_mut())?;
+    raw.suspend_raw_mode()?;
+
+    signals::stop()?;
+    resume(terminal, raw)
+}
+
+// resume takes the terminal over again and has the whole screen redrawn,
+// shell may have changed the terminal while the game was stopped
+fn resume(terminal: &mut Screen, raw: &RawTerminal<io::Stdout>) -> io::Result<()> {
+    raw.activate_raw_mode()?;
+    write!(terminal.backend_mut(), "{}", ToAlternateScreen)?;
+    terminal.clear()
+}
+
+enum Game {
+    Solo(Box<Board>),
+    Versus(Box<Versus>),
+    Online(Box<Online>),
+}
+
+impl Game {
+    fn boards(&self) -> Vec<&Board> {
+        match self {
+            Game::Solo(b) => vec![b],
+            Game::Versus(v) => v.boards.iter().collect(),
+            Game::Online(o) => vec![&o.board],
+        }
+    }
+
+    // is_still tells that time doesn't change anything on the screen,
+    // online opponent may change it any moment
+    fn is_still(&self) -> bool {
+        let stopped = |b: &Board| b.is_game_over() || b.is_paused();
+        match self {
+            Game::Solo(b) => stopped(b),
+            Game::Versus(v) => v.boards.iter().all(stopped),
+            Game::Online(_) => false,
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        match self {
+            Game::Solo(b) => b.is_paused(),
+            Game::Versus(v) => v.boards[0].is_paused(),
+            Game::Online(_) => false,
+        }
+    }
+
+    // pause stops the game unless it is already stopped
+    fn pause(&mut self) {
+        if !self.is_paused() && !self.is_over() {
+            self.toggle_pause();
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        match self {
+            Game::Solo(b) => b.toggle_pause(),
+            Game::Versus(v) => v.toggle_pause(),
+            Game::Online(_) => {}
+        }
+    }
+
+    // restart gives the same game to play from the beginning, puzzles
+    // and setups only start over from the file they were loaded from
+    fn restart(&self, start: Option<&Board>, seed: Option<u64>) -> Option<Game> {
+        let settings = Settings {
+            seed,
+            ..*self.boards()[0].settings()
+        };
+        match self {
+            Game::Solo(_) if start.is_some() => start.cloned().map(|b| Game::Solo(Box::new(b))),
+            Game::Solo(_) if !matches!(settings.mode, Mode::Puzzle | Mode::Trainer) => {
+                Some(Game::Solo(Box::new(Board::new(settings))))
+            }
+            Game::Versus(_) => Some(Game::Versus(Box::new(Versus::new(settings)))),
+            _ => None,
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        match self {
+            Game::Solo(b) => b.is_game_over(),
+            Game::Versus(v) => v.is_over(),
+            Game::Online(o) => o.board.is_game_over(),
+        }
+    }
 }
 
-fn check_rows<B: Backend>(
+// handle_game_event returns false when player wants to leave the game
+fn handle_game_event<B: Backend>(
     terminal: &mut Terminal<B>,
+    theme: &Theme,
     board: &mut Board,
+    bot: Option<&mut Bot>,
+    fumens: &mut Vec<String>,
+    event: Event<Key>,
+) -> Result<bool, Box<dyn Error>> {
+    match event {
+        Event::Input(Key::Char('q')) => {
+            return Ok(false);
+        }
+        Event::Input(Key::Esc) => board.toggle_pause(),
+        Event::Input(Key::Char('f')) => fumens.push(board.to_fumen()),
+        Event::Input(_) if bot.is_some() => {
+            // keyboard doesn't control the game when bot is playing
+        }
+        Event::Input(Key::Char('h')) => board.toggle_hint(),
+        Event::Input(Key::Char('u')) => board.undo(),
+        Event::Input(Key::Char('r')) => board.redo(),
+        Event::Input(key) => {
+            if let Some(m) = keymap::SOLO.action(key) {
+                board.press(m);
+
+                // we should check whether we can delete rows
+                // after hard drop, too
+                if m == Move::HardDrop {
+                    check_rows(board, |b| redraw(terminal, theme, b))?;
+                }
+            }
+        }
+        Event::Frame(dt) => {
+            // if tetronimo fell to the end
+            if board.update(dt) {
+                check_rows(board, |b| redraw(terminal, theme, b))?;
+            }
+        }
+        Event::Step => {
+            let bot = match bot {
+                Some(b) => b,
+                None => return Ok(true),
+            };
+
+            if board.is_paused() || board.is_game_over() {
+                return Ok(true);
+            }
+
+            match bot.next_move(board) {
+                Some(m) => {
+                    board.apply(m);
+                    if m == Move::HardDrop {
+                        check_rows(board, |b| redraw(terminal, theme, b))?;
+                    }
+                }
+                // bot doesn't want to wait for gravity to spawn the next piece
+                None => {
+                    if board.update(Duration::ZERO) {
+                        check_rows(board, |b| redraw(terminal, theme, b))?;
+                    }
+                }
+            }
+        }
+        // the main loop takes care of these
+        Event::Focus(_) | Event::Signal(_) | Event::Error(_) => {}
+    }
+
+    Ok(true)
+}
+
+// handle_versus_event routes keys to the board of the player who pressed them
+fn handle_versus_event<B: Backend>(
+    terminal: &mut Terminal<B>,
+    theme: &Theme,
+    v: &mut Versus,
+    event: Event<Key>,
+) -> Result<bool, Box<dyn Error>> {
+    match event {
+        Event::Input(Key::Char('q')) => {
+            return Ok(false);
+        }
+        Event::Input(Key::Esc) => v.toggle_pause(),
+        Event::Input(key) => {
+            if let Some((i, m)) = v.action(key) {
+                v.boards[i].press(m);
+                if m == Move::HardDrop {
+                    check_versus_rows(terminal, theme, v, i)?;
+                }
+            }
+        }
+        Event::Frame(dt) => {
+            for i in 0..2 {
+                if v.boards[i].update(dt) {
+                    check_versus_rows(terminal, theme, v, i)?;
+                }
+            }
+        }
+        Event::Step | Event::Focus(_) | Event::Signal(_) | Event::Error(_) => {}
+    }
+
+    v.update();
+
+    Ok(true)
+}
+
+// handle_online_event plays our board with the solo keys,
+// there is no pause as the opponent wouldn't stop anyway
+fn handle_online_event<B: Backend>(
+    terminal: &mut Terminal<B>,
+    theme: &Theme,
+    o: &mut Online,
+    event: Event<Key>,
+) -> Result<bool, Box<dyn Error>> {
+    let Online {
+        board,
+        opponent,
+        status,
+        ..
+    } = o;
+    let mut redraw = |b: &Board| -> io::Result<()> {
+        terminal.draw(|f| ui::online::draw(f, b, opponent.as_ref(), *status, theme))?;
+        Ok(())
+    };
+
+    match event {
+        Event::Input(Key::Char('q')) => {
+            return Ok(false);
+        }
+        Event::Input(key) => {
+            if let Some(m) = keymap::SOLO.action(key) {
+                board.press(m);
+                if m == Move::HardDrop {
+                    check_rows(board, &mut redraw)?;
+                }
+            }
+        }
+        Event::Frame(dt) => {
+            if board.update(dt) {
+                check_rows(board, &mut redraw)?;
+            }
+        }
+        Event::Step | Event::Focus(_) | Event::Signal(_) | Event::Error(_) => {}
+    }
+
+    o.update();
+
+    Ok(true)
+}
+
+fn check_versus_rows<B: Backend>(
+    terminal: &mut Terminal<B>,
+    theme: &Theme,
+    v: &mut Versus,
+    i: usize,
+) -> Result<(), Box<dyn Error>> {
+    let (left, right) = v.boards.split_at_mut(1);
+    let (board, other) = if i == 0 {
+        (&mut left[0], &right[0])
+    } else {
+        (&mut right[0], &left[0])
+    };
+
+    check_rows(board, |b| {
+        let boards = if i == 0 { [b, other] } else { [other, b] };
+        terminal.draw(|f| ui::versus::draw(f, boards, theme))?;
+        Ok(())
+    })
+}
+
+fn redraw<B: Backend>(terminal: &mut Terminal<B>, theme: &Theme, board: &Board) -> io::Result<()> {
+    terminal.draw(|f| ui::draw(f, board, theme))?;
+    Ok(())
+}
+
+// leave_game is called when player leaves a game which is not over yet,
+// solo games are saved to be resumed later instead of being finished
+fn leave_game(
+    game: &mut Game,
+    scores: &mut HighScores,
+    export: Option<&Path>,
+    autoplay: bool,
+) -> Result<(), Box<dyn Error>> {
+    match game {
+        Game::Solo(board) if !autoplay => {
+            // resumed game starts paused, so that player can get ready
+            if !board.is_paused() {
+                board.toggle_pause();
+            }
+            save::save(board)
+        }
+        _ => finish_game(game, scores, export, autoplay),
+    }
+}
+
+// finish_game is called once every time a game ends,
+// either when it is over or when player leaves it
+fn finish_game(
+    game: &Game,
+    scores: &mut HighScores,
+    export: Option<&Path>,
+    autoplay: bool,
 ) -> Result<(), Box<dyn Error>> {
+    for board in game.boards() {
+        // neither bot nor player who used hints get to the high scores table
+        let assisted = autoplay || board.is_assisted();
+        if !assisted && board.is_game_over() && should_record(board) {
+            scores.record(Entry::from_board(board))?;
+        }
+
+        if let Some(path) = export {
+            export::append(path, &export::Record::from_board(board))?;
+        }
+    }
+
+    Ok(())
+}
+
+// only games which reached their goal are worth a high score,
+// marathon has no goal so any finished game counts
+fn should_record(board: &Board) -> bool {
+    match board.settings().mode {
+        Mode::Marathon => true,
+        Mode::Zen | Mode::Versus | Mode::Puzzle | Mode::Trainer => false,
+        _ => board.is_completed(),
+    }
+}
+
+// check_rows deletes full rows one by one, redrawing
+// the screen in between so that player can see it happen
+fn check_rows<F>(board: &mut Board, mut redraw: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&Board) -> io::Result<()>,
+{
     let mut amount_deleted = 0;
-    loop {
-        let delete_row = match board.can_delete() {
-            Some(r) => r,
-            None => break,
-        };
+    while let Some(delete_row) = board.can_delete() {
         amount_deleted += 1;
 
         board.delete(delete_row);
-        terminal.draw(|f| ui::draw(f, &board))?;
+        redraw(board)?;
         thread::sleep(Duration::from_millis(20));
         board.collapse(delete_row);
-        terminal.draw(|f| ui::draw(f, &board))?;
+        redraw(board)?;
         thread::sleep(Duration::from_millis(20));
     }
-    board.add_score(ScoreAction::RowCleared(amount_deleted));
+    board.rows_cleared(amount_deleted);
 
     Ok(())
 }
@@ -1,6 +1,6 @@
+use std::panic;
 use std::process;
-use tetetris;
-use tetetris::flags;
+use tetetris::{crash, flags};
 
 fn main() {
     let f = match flags::config_flags() {
@@ -11,8 +11,20 @@ fn main() {
         }
     };
 
-    if let Err(e) = tetetris::run(f) {
-        eprintln!("Error: {}", e);
-        process::exit(1);
+    crash::install();
+    let result = panic::catch_unwind(|| tetetris::run(f));
+    // terminal is restored by now, panics in other threads
+    // are reported too even if the game went on
+    if let Some(report) = crash::report() {
+        eprintln!("{}", report);
+    }
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+        Err(_) => process::exit(101),
     }
 }
@@ -0,0 +1,176 @@
+use crate::game::settings::{self, Mode, RotationSystem, Settings};
+use termion::event::Key;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Screen {
+    Main,
+    Options,
+    HighScores,
+    Controls,
+}
+
+pub enum Action {
+    None,
+    Start(Settings),
+    Versus(Settings),
+    Quit,
+}
+
+// main menu entries are all the game modes
+// followed by these additional items
+pub const MAIN_EXTRA: [&str; 5] = ["Versus", "Options", "High scores", "Controls", "Quit"];
+pub const OPTIONS: [&str; 5] = ["Level", "Gravity", "Preview", "Rotation", "Garbage delay"];
+
+const GRAVITY_STEP: u32 = 50;
+const MAX_GRAVITY: u32 = 2000;
+const GARBAGE_DELAY_STEP: u32 = 100;
+
+pub struct Menu {
+    screen: Screen,
+    selected: usize,
+    option: usize,
+    scores_mode: usize,
+
+    settings: Settings,
+}
+
+impl Menu {
+    pub fn new(settings: Settings) -> Menu {
+        Menu {
+            screen: Screen::Main,
+            selected: 0,
+            option: 0,
+            scores_mode: 0,
+            settings,
+        }
+    }
+
+    pub fn screen(&self) -> Screen {
+        self.screen
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn option(&self) -> usize {
+        self.option
+    }
+
+    pub fn scores_mode(&self) -> Mode {
+        Mode::ALL[self.scores_mode]
+    }
+
+    // option_value renders current value of the option for the options screen
+    pub fn option_value(&self, option: usize) -> String {
+        match option {
+            0 => self.settings.level.to_string(),
+            1 => format!("{} ms", self.settings.gravity),
+            2 => self.settings.preview.to_string(),
+            3 => self.settings.rotation.name().to_string(),
+            _ => format!("{} ms", self.settings.garbage_delay),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> Action {
+        match self.screen {
+            Screen::Main => return self.handle_main(key),
+            Screen::Options => self.handle_options(key),
+            Screen::HighScores => self.handle_scores(key),
+            Screen::Controls => self.handle_back(key),
+        }
+
+        Action::None
+    }
+
+    fn handle_main(&mut self, key: Key) -> Action {
+        let items = Mode::ALL.len() + MAIN_EXTRA.len();
+
+        match key {
+            Key::Up | Key::Char('k') => self.selected = (self.selected + items - 1) % items,
+            Key::Down | Key::Char('j') => self.selected = (self.selected + 1) % items,
+            Key::Char('q') | Key::Esc => return Action::Quit,
+            Key::Char('\n') | Key::Char(' ') => {
+                if self.selected < Mode::ALL.len() {
+                    self.settings.mode = Mode::ALL[self.selected];
+                    return Action::Start(self.settings);
+                }
+
+                match self.selected - Mode::ALL.len() {
+                    0 => return Action::Versus(self.settings),
+                    1 => self.screen = Screen::Options,
+                    2 => self.screen = Screen::HighScores,
+                    3 => self.screen = Screen::Controls,
+                    _ => return Action::Quit,
+                }
+            }
+            _ => {}
+        }
+
+        Action::None
+    }
+
+    fn handle_options(&mut self, key: Key) {
+        let items = OPTIONS.len();
+
+        match key {
+            Key::Up | Key::Char('k') => self.option = (self.option + items - 1) % items,
+            Key::Down | Key::Char('j') => self.option = (self.option + 1) % items,
+            Key::Left | Key::Char('h') => self.change_option(false),
+            Key::Right | Key::Char('l') => self.change_option(true),
+            _ => self.handle_back(key),
+        }
+    }
+
+    fn change_option(&mut self, increase: bool) {
+        let s = &mut self.settings;
+        match self.option {
+            0 => s.level = step(s.level, 1, 1, settings::MAX_LEVEL, increase),
+            1 => s.gravity = step(s.gravity, GRAVITY_STEP, GRAVITY_STEP, MAX_GRAVITY, increase),
+            2 => s.preview = step(s.preview, 1, 1, settings::MAX_PREVIEW, increase),
+            3 => {
+                s.rotation = match s.rotation {
+                    RotationSystem::Srs => RotationSystem::Classic,
+                    RotationSystem::Classic => RotationSystem::Srs,
+                }
+            }
+            _ => {
+                let max = settings::MAX_GARBAGE_DELAY;
+                s.garbage_delay = step(s.garbage_delay, GARBAGE_DELAY_STEP, 0, max, increase)
+            }
+        }
+    }
+
+    fn handle_scores(&mut self, key: Key) {
+        let modes = Mode::ALL.len();
+
+        match key {
+            Key::Left | Key::Char('h') => self.scores_mode = (self.scores_mode + modes - 1) % modes,
+            Key::Right | Key::Char('l') => self.scores_mode = (self.scores_mode + 1) % modes,
+            _ => self.handle_back(key),
+        }
+    }
+
+    fn handle_back(&mut self, key: Key) {
+        if let Key::Esc | Key::Char('q') | Key::Char('\n') | Key::Backspace = key {
+            self.screen = Screen::Main;
+        }
+    }
+}
+
+fn step<T>(value: T, delta: T, min: T, max: T, increase: bool) -> T
+where
+    T: Copy + PartialOrd + std::ops::Add<Output = T> + std::ops::Sub<Output = T>,
+{
+    if increase {
+        if value + delta > max {
+            max
+        } else {
+            value + delta
+        }
+    } else if value < min + delta {
+        min
+    } else {
+        value - delta
+    }
+}
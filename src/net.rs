@@ -0,0 +1,184 @@
+use crate::game::settings::RotationSystem;
+use crate::game::Snapshot;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+
+// anything bigger than this is not a message we could have sent
+const MAX_MESSAGE_LEN: usize = 1 << 20;
+
+// Message is a single frame of the versus protocol
+#[derive(Serialize, Deserialize)]
+pub enum Message {
+    // host tells the joining player what game they are going to play
+    Hello {
+        seed: u64,
+        gravity: u32,
+        preview: usize,
+        rotation: RotationSystem,
+        garbage_delay: u32,
+    },
+    // garbage lines sent to the opponent
+    Attack(u32),
+    Board(Snapshot),
+    // sender topped out
+    GameOver,
+}
+
+// every frame sent over the network is a 4 bytes
+// big endian length followed by JSON payload
+pub fn write_message<W: Write, T: Serialize>(w: &mut W, m: &T) -> io::Result<()> {
+    let payload =
+        serde_json::to_vec(m).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    w.write_all(&(payload.len() as u32).to_be_bytes())?;
+    w.write_all(&payload)?;
+    w.flush()
+}
+
+pub fn read_message<R: Read, T: DeserializeOwned>(r: &mut R) -> io::Result<T> {
+    let mut len = [0; 4];
+    r.read_exact(&mut len)?;
+
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message is too long",
+        ));
+    }
+
+    let mut payload = vec![0; len];
+    r.read_exact(&mut payload)?;
+    serde_json::from_slice(&payload).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// Peer is the other side of the connection, messages
+// from it are read by a separate thread so that the game never blocks
+pub struct Peer {
+    stream: TcpStream,
+    rx: mpsc::Receiver<Message>,
+}
+
+impl Peer {
+    pub fn new(stream: TcpStream) -> io::Result<Peer> {
+        stream.set_nodelay(true)?;
+
+        let (tx, rx) = mpsc::channel();
+        let mut reader = stream.try_clone()?;
+        thread::spawn(move || {
+            // sender is dropped when connection breaks,
+            // that is how the game learns about it
+            while let Ok(m) = read_message(&mut reader) {
+                if tx.send(m).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Peer { stream, rx })
+    }
+
+    pub fn send(&mut self, m: &Message) -> io::Result<()> {
+        write_message(&mut self.stream, m)
+    }
+
+    // receive returns the next message if there is one,
+    // or an error once the connection is closed
+    pub fn receive(&self) -> io::Result<Option<Message>> {
+        match self.rx.try_recv() {
+            Ok(m) => Ok(Some(m)),
+            Err(mpsc::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::TryRecvError::Disconnected) => Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "opponent disconnected",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    // connected returns both ends of a loopback connection,
+    // the accepted one first
+    pub fn connected() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let (mut server, mut client) = connected();
+
+        let hello = Message::Hello {
+            seed: 42,
+            gravity: 300,
+            preview: 3,
+            rotation: RotationSystem::Classic,
+            garbage_delay: 500,
+        };
+        write_message(&mut server, &hello).unwrap();
+        write_message(&mut server, &Message::Attack(4)).unwrap();
+        write_message(&mut server, &Message::GameOver).unwrap();
+
+        match read_message(&mut client).unwrap() {
+            Message::Hello {
+                seed: 42,
+                gravity: 300,
+                preview: 3,
+                rotation: RotationSystem::Classic,
+                garbage_delay: 500,
+            } => {}
+            _ => panic!("hello changed on the way"),
+        }
+        assert!(matches!(
+            read_message(&mut client).unwrap(),
+            Message::Attack(4)
+        ));
+        assert!(matches!(
+            read_message(&mut client).unwrap(),
+            Message::GameOver
+        ));
+    }
+
+    #[test]
+    fn too_long_message_is_refused() {
+        let (mut server, mut client) = connected();
+        server
+            .write_all(&(MAX_MESSAGE_LEN as u32 + 1).to_be_bytes())
+            .unwrap();
+
+        let e = read_message::<_, Message>(&mut client).err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn peer_notices_disconnect() {
+        let (server, client) = connected();
+        let peer = Peer::new(server).unwrap();
+        drop(client);
+
+        let start = Instant::now();
+        loop {
+            match peer.receive() {
+                Err(e) => {
+                    assert_eq!(e.kind(), io::ErrorKind::ConnectionAborted);
+                    return;
+                }
+                Ok(_) if start.elapsed() > Duration::from_secs(5) => {
+                    panic!("disconnect wasn't noticed")
+                }
+                Ok(_) => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    }
+}
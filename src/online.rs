@@ -0,0 +1,257 @@
+use crate::game::settings::{self, Mode, Settings};
+use crate::game::{Board, Snapshot};
+use crate::net::{self, Message, Peer};
+use rand::{thread_rng, Rng};
+use std::io;
+use std::net::{TcpListener, TcpStream};
+
+// Online is a versus game against a player on another machine,
+// only our board is played here, opponent's one is just a picture
+pub struct Online {
+    pub board: Board,
+    // the last board opponent has sent us
+    pub opponent: Option<Snapshot>,
+    // shown instead of the opponent's board state once connection is lost
+    pub status: Option<&'static str>,
+    peer: Option<Peer>,
+    sent: Option<Snapshot>,
+    sent_over: bool,
+}
+
+impl Online {
+    // host waits for somebody to join and picks the game for both
+    pub fn host(port: u16, settings: Settings) -> io::Result<Online> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let (mut stream, _) = listener.accept()?;
+
+        let seed = settings.seed.unwrap_or_else(|| thread_rng().gen());
+        let hello = Message::Hello {
+            seed,
+            gravity: settings.gravity,
+            preview: settings.preview,
+            rotation: settings.rotation,
+            garbage_delay: settings.garbage_delay,
+        };
+        net::write_message(&mut stream, &hello)?;
+
+        let settings = Settings {
+            seed: Some(seed),
+            ..settings
+        };
+        Ok(Online::new(Peer::new(stream)?, settings))
+    }
+
+    // join connects to the host and plays whatever game it picked
+    pub fn join(addr: &str, settings: Settings) -> io::Result<Online> {
+        let mut stream = TcpStream::connect(addr)?;
+
+        let settings = match net::read_message(&mut stream)? {
+            Message::Hello {
+                seed,
+                gravity,
+                preview,
+                rotation,
+                garbage_delay,
+            } if preview <= settings::MAX_PREVIEW
+                && garbage_delay <= settings::MAX_GARBAGE_DELAY =>
+            {
+                Settings {
+                    seed: Some(seed),
+                    gravity,
+                    preview,
+                    rotation,
+                    garbage_delay,
+                    ..settings
+                }
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "host didn't say a proper hello",
+                ))
+            }
+        };
+        Ok(Online::new(Peer::new(stream)?, settings))
+    }
+
+    fn new(peer: Peer, settings: Settings) -> Online {
+        let settings = Settings {
+            mode: Mode::Versus,
+            ..settings
+        };
+
+        Online {
+            board: Board::new(settings),
+            opponent: None,
+            status: None,
+            peer: Some(peer),
+            sent: None,
+            sent_over: false,
+        }
+    }
+
+    // update handles everything opponent has sent since the last call
+    // and sends our attacks and board to them
+    pub fn update(&mut self) {
+        if self.exchange().is_err() {
+            self.peer = None;
+            self.status = Some("Opponent left");
+            // nobody is left to play against
+            self.board.win();
+        }
+    }
+
+    fn exchange(&mut self) -> io::Result<()> {
+        let peer = match self.peer.as_mut() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+
+        while let Some(m) = peer.receive()? {
+            match m {
+                Message::Attack(lines) => self.board.receive_garbage(lines),
+                Message::Board(s) if s.is_valid() => self.opponent = Some(s),
+                // broken peer is not played against any more
+                Message::Board(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "opponent sent a board with cells off the field",
+                    ))
+                }
+                Message::GameOver => self.board.win(),
+                Message::Hello { .. } => {}
+            }
+        }
+
+        let attack = self.board.take_attack();
+        if attack > 0 {
+            peer.send(&Message::Attack(attack))?;
+        }
+
+        // board is sent only when it has changed
+        let snapshot = self.board.snapshot();
+        if self.sent.as_ref() != Some(&snapshot) {
+            peer.send(&Message::Board(snapshot.clone()))?;
+            self.sent = Some(snapshot);
+        }
+
+        if self.board.is_game_over() && !self.sent_over {
+            self.sent_over = true;
+            if !self.board.is_completed() {
+                peer.send(&Message::GameOver)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Cell, Snapshot};
+    use crate::net::tests::connected;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn game(stream: TcpStream) -> Online {
+        let settings = Settings {
+            seed: Some(7),
+            ..Settings::new(300)
+        };
+        Online::new(Peer::new(stream).unwrap(), settings)
+    }
+
+    // update_until updates the game until it gets what is waited for,
+    // messages come through a reader thread so they take a while
+    fn update_until(o: &mut Online, done: impl Fn(&Online) -> bool) {
+        let start = Instant::now();
+        while !done(o) {
+            assert!(start.elapsed() < Duration::from_secs(5), "nothing came");
+            o.update();
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn boards_are_exchanged() {
+        let (server, client) = connected();
+        let (mut a, mut b) = (game(server), game(client));
+
+        a.update();
+        update_until(&mut b, |b| b.opponent.is_some());
+        b.update();
+        update_until(&mut a, |a| a.opponent.is_some());
+
+        assert!(b.opponent.as_ref() == Some(&a.board.snapshot()));
+        assert!(a.status.is_none() && b.status.is_none());
+    }
+
+    #[test]
+    fn opponent_leaving_wins_the_game() {
+        let (server, client) = connected();
+        let (mut a, b) = (game(server), game(client));
+        drop(b);
+
+        update_until(&mut a, |a| a.status.is_some());
+        assert!(a.board.is_completed());
+    }
+
+    #[test]
+    fn board_off_the_field_disconnects() {
+        let (server, mut client) = connected();
+        let mut a = game(server);
+
+        let broken = Snapshot {
+            cells: vec![(-1, 25, Cell::Garbage)],
+            score: 0,
+            lines: 0,
+            incoming: 0,
+            game_over: false,
+            completed: false,
+        };
+        net::write_message(&mut client, &Message::Board(broken)).unwrap();
+
+        update_until(&mut a, |a| a.status.is_some());
+        assert!(a.opponent.is_none());
+    }
+
+    // host_saying sends the hello to whoever joins
+    fn host_saying(hello: Message) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            net::write_message(&mut stream, &hello).unwrap();
+        });
+        addr
+    }
+
+    fn hello(preview: usize, garbage_delay: u32) -> Message {
+        Message::Hello {
+            seed: 7,
+            gravity: 300,
+            preview,
+            rotation: settings::RotationSystem::Classic,
+            garbage_delay,
+        }
+    }
+
+    #[test]
+    fn host_settings_are_taken() {
+        let addr = host_saying(hello(2, 1000));
+        let o = Online::join(&addr, Settings::new(300)).unwrap();
+
+        assert_eq!(o.board.settings().preview, 2);
+        assert_eq!(o.board.settings().garbage_delay, 1000);
+    }
+
+    #[test]
+    fn host_settings_out_of_bounds_are_refused() {
+        let addr = host_saying(hello(settings::MAX_PREVIEW + 1, 500));
+        assert!(Online::join(&addr, Settings::new(300)).is_err());
+
+        let addr = host_saying(hello(1, settings::MAX_GARBAGE_DELAY + 1));
+        assert!(Online::join(&addr, Settings::new(300)).is_err());
+    }
+}
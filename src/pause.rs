@@ -0,0 +1,79 @@
+use termion::event::Key;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Page {
+    Main,
+    Settings,
+    Controls,
+}
+
+pub enum Action {
+    None,
+    Resume,
+    Restart,
+    Quit,
+}
+
+pub const ITEMS: [&str; 5] = ["Resume", "Restart", "Settings", "Controls", "Quit"];
+
+// PauseMenu is shown while the game is paused,
+// it starts from the top every time it is left
+pub struct PauseMenu {
+    page: Page,
+    selected: usize,
+}
+
+impl PauseMenu {
+    pub fn new() -> PauseMenu {
+        PauseMenu {
+            page: Page::Main,
+            selected: 0,
+        }
+    }
+
+    pub fn page(&self) -> Page {
+        self.page
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> Action {
+        let action = match self.page {
+            Page::Main => self.handle_main(key),
+            _ => {
+                if let Key::Esc | Key::Char('q') | Key::Char('\n') | Key::Backspace = key {
+                    self.page = Page::Main;
+                }
+                Action::None
+            }
+        };
+
+        if !matches!(action, Action::None) {
+            *self = PauseMenu::new();
+        }
+        action
+    }
+
+    fn handle_main(&mut self, key: Key) -> Action {
+        let items = ITEMS.len();
+
+        match key {
+            Key::Up | Key::Char('k') => self.selected = (self.selected + items - 1) % items,
+            Key::Down | Key::Char('j') => self.selected = (self.selected + 1) % items,
+            Key::Esc => return Action::Resume,
+            Key::Char('q') => return Action::Quit,
+            Key::Char('\n') | Key::Char(' ') => match self.selected {
+                0 => return Action::Resume,
+                1 => return Action::Restart,
+                2 => self.page = Page::Settings,
+                3 => self.page = Page::Controls,
+                _ => return Action::Quit,
+            },
+            _ => {}
+        }
+
+        Action::None
+    }
+}
@@ -0,0 +1,98 @@
+use crate::game::Board;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SAVE_FILE: &str = ".tetetris_save";
+
+// there is a single saved game in user's home directory,
+// saving a game replaces the previous one
+fn path() -> Result<PathBuf, Box<dyn Error>> {
+    let home = env::var_os("HOME").ok_or("HOME is not set, can't find saved game")?;
+    Ok(PathBuf::from(home).join(SAVE_FILE))
+}
+
+pub fn save(board: &Board) -> Result<(), Box<dyn Error>> {
+    save_to(board, &path()?)
+}
+
+// load takes the game out of the file,
+// so that the same game can't be resumed twice
+pub fn load() -> Result<Board, Box<dyn Error>> {
+    load_from(&path()?)
+}
+
+fn save_to(board: &Board, path: &Path) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_vec(board)?)?;
+    Ok(())
+}
+
+fn load_from(path: &Path) -> Result<Board, Box<dyn Error>> {
+    let content =
+        fs::read(path).map_err(|e| format!("Can't read saved game {}: {}", path.display(), e))?;
+    let board: Board = serde_json::from_slice(&content)
+        .map_err(|e| format!("Saved game {} is broken: {}", path.display(), e))?;
+    board
+        .check()
+        .map_err(|e| format!("Saved game {} is broken: {}", path.display(), e))?;
+
+    fs::remove_file(path)?;
+    Ok(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::settings::Settings;
+    use crate::game::Move;
+    use std::time::Duration;
+
+    fn upcoming(board: &Board) -> Vec<(i8, i8)> {
+        board
+            .upcoming_pieces()
+            .flat_map(|(_, pixels)| pixels.iter().map(|p| (p.x, p.y)))
+            .collect()
+    }
+
+    #[test]
+    fn game_is_resumed_as_it_was_saved() {
+        let path = env::temp_dir().join(format!("tetetris-save-{}", std::process::id()));
+
+        let mut board = Board::new(Settings::new(300));
+        board.update(Duration::ZERO);
+        for m in [
+            Move::Left,
+            Move::HardDrop,
+            Move::Right,
+            Move::HardDrop,
+            Move::Left,
+        ] {
+            board.press(m);
+        }
+        board.update(Duration::from_millis(500));
+        board.toggle_pause();
+
+        save_to(&board, &path).unwrap();
+        let mut loaded = load_from(&path).unwrap();
+        assert!(!path.exists());
+
+        // locked cells and the falling piece
+        assert!(loaded.field() == board.field());
+        assert!(loaded.is_paused());
+        // clock doesn't run on pause, neither before saving nor after
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(loaded.elapsed(), board.elapsed());
+        assert_eq!(loaded.score(), board.score());
+
+        // both get the same pieces from the bag from now on
+        board.toggle_pause();
+        loaded.toggle_pause();
+        for _ in 0..10 {
+            assert!(upcoming(&loaded) == upcoming(&board));
+            board.press(Move::HardDrop);
+            loaded.press(Move::HardDrop);
+        }
+        assert!(loaded.field() == board.field());
+    }
+}
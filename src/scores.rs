@@ -0,0 +1,113 @@
+use crate::game::settings::Mode;
+use crate::game::Board;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SCORES_FILE: &str = ".tetetris_scores";
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Clone)]
+pub struct Entry {
+    pub mode: Mode,
+    pub score: u64,
+    pub lines: u32,
+    pub time: Duration,
+}
+
+impl Entry {
+    pub fn from_board(board: &Board) -> Entry {
+        Entry {
+            mode: board.settings().mode,
+            score: board.score(),
+            lines: board.lines(),
+            time: board.elapsed(),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Entry> {
+        let mut fields = line.split('\t');
+
+        let mode = fields.next()?;
+        let mode = *Mode::ALL.iter().find(|m| m.name() == mode)?;
+        let score = fields.next()?.parse().ok()?;
+        let lines = fields.next()?.parse().ok()?;
+        let millis = fields.next()?.parse().ok()?;
+
+        Some(Entry {
+            mode,
+            score,
+            lines,
+            time: Duration::from_millis(millis),
+        })
+    }
+
+    fn format(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.mode.name(),
+            self.score,
+            self.lines,
+            self.time.as_millis()
+        )
+    }
+}
+
+// HighScores keeps best results for every game mode
+// in a plain text file in user's home directory
+pub struct HighScores {
+    path: Option<PathBuf>,
+    entries: Vec<Entry>,
+}
+
+impl HighScores {
+    pub fn load() -> HighScores {
+        let path = env::var_os("HOME").map(|h| PathBuf::from(h).join(SCORES_FILE));
+
+        let entries = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .map(|s| s.lines().filter_map(Entry::parse).collect())
+            .unwrap_or_default();
+
+        HighScores { path, entries }
+    }
+
+    // top returns best results for the mode, best first
+    pub fn top(&self, mode: Mode) -> Vec<&Entry> {
+        let mut res: Vec<&Entry> = self.entries.iter().filter(|e| e.mode == mode).collect();
+
+        match mode {
+            // sprint and dig are races, the faster the better
+            Mode::Sprint | Mode::Dig => res.sort_by_key(|e| e.time),
+            _ => res.sort_by_key(|e| std::cmp::Reverse(e.score)),
+        }
+
+        res.truncate(MAX_ENTRIES);
+        res
+    }
+
+    pub fn record(&mut self, entry: Entry) -> io::Result<()> {
+        let mode = entry.mode;
+        self.entries.push(entry);
+
+        // only keep the best entries for the mode
+        let kept: Vec<Entry> = self.top(mode).into_iter().cloned().collect();
+        self.entries.retain(|e| e.mode != mode);
+        self.entries.extend(kept);
+
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+
+        let content: Vec<String> = self.entries.iter().map(|e| e.format()).collect();
+        fs::write(path, content.join("\n") + "\n")
+    }
+}
@@ -0,0 +1,131 @@
+use std::io;
+use std::ptr;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Signal {
+    // terminal window changed its size
+    Resize,
+    // Ctrl-Z from outside the game, or kill -TSTP
+    Suspend,
+    // game goes on after it was stopped
+    Continue,
+    // game is asked to quit
+    Terminate,
+}
+
+const CAUGHT: [libc::c_int; 6] = [
+    libc::SIGWINCH,
+    libc::SIGTSTP,
+    libc::SIGCONT,
+    libc::SIGTERM,
+    libc::SIGINT,
+    libc::SIGHUP,
+];
+
+// write end of the pipe, handler can't reach anything else
+static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+// Signals catches the signals the game answers itself until dropped,
+// handler only writes them to a pipe which is polled together with stdin
+pub struct Signals {
+    read: libc::c_int,
+    write: libc::c_int,
+}
+
+impl Signals {
+    pub fn catch() -> io::Result<Signals> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let signals = Signals {
+            read: fds[0],
+            write: fds[1],
+        };
+
+        // full pipe must not block the handler, signals
+        // coming that fast are the same ones anyway
+        for fd in fds {
+            unsafe {
+                libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK);
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+        }
+
+        PIPE.store(signals.write, Ordering::Relaxed);
+        for sig in CAUGHT {
+            set_handler(sig, handle as *const () as libc::sighandler_t)?;
+        }
+        Ok(signals)
+    }
+
+    pub fn fd(&self) -> libc::c_int {
+        self.read
+    }
+
+    // read takes the signals caught since it was called last time
+    pub fn read(&self) -> Vec<Signal> {
+        let mut caught = Vec::new();
+        let mut buf = [0u8; 64];
+        loop {
+            let n = unsafe { libc::read(self.read, buf.as_mut_ptr().cast(), buf.len()) };
+            if n <= 0 {
+                return caught;
+            }
+            caught.extend(buf[..n as usize].iter().filter_map(|&b| signal(b.into())));
+        }
+    }
+}
+
+impl Drop for Signals {
+    fn drop(&mut self) {
+        for sig in CAUGHT {
+            let _ = set_handler(sig, libc::SIG_DFL);
+        }
+        PIPE.store(-1, Ordering::Relaxed);
+        unsafe {
+            libc::close(self.read);
+            libc::close(self.write);
+        }
+    }
+}
+
+// stop stops the game the way Ctrl-Z would have without the handler,
+// it returns once the game is continued
+pub fn stop() -> io::Result<()> {
+    set_handler(libc::SIGTSTP, libc::SIG_DFL)?;
+    unsafe { libc::raise(libc::SIGTSTP) };
+    set_handler(libc::SIGTSTP, handle as *const () as libc::sighandler_t)
+}
+
+fn signal(sig: libc::c_int) -> Option<Signal> {
+    match sig {
+        libc::SIGWINCH => Some(Signal::Resize),
+        libc::SIGTSTP => Some(Signal::Suspend),
+        libc::SIGCONT => Some(Signal::Continue),
+        libc::SIGTERM | libc::SIGINT | libc::SIGHUP => Some(Signal::Terminate),
+        _ => None,
+    }
+}
+
+fn set_handler(sig: libc::c_int, handler: libc::sighandler_t) -> io::Result<()> {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(sig, &action, ptr::null_mut()) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+extern "C" fn handle(sig: libc::c_int) {
+    let fd = PIPE.load(Ordering::Relaxed);
+    if fd >= 0 {
+        let b = sig as u8;
+        unsafe { libc::write(fd, (&b as *const u8).cast(), 1) };
+    }
+}
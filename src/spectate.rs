@@ -0,0 +1,293 @@
+use crate::game::Board;
+use crate::net;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// watchers who can't take a frame for this long are dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Frame = Arc<Vec<u8>>;
+type Watchers = Arc<Mutex<Vec<Arc<Slot>>>>;
+
+// Slot keeps the one frame a slow watcher is yet to be sent, a newer
+// frame replaces it as every frame has the whole game
+#[derive(Default)]
+struct Slot {
+    latest: Mutex<Latest>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct Latest {
+    frame: Option<Frame>,
+    closed: bool,
+}
+
+impl Slot {
+    // put returns false once the watcher is gone
+    fn put(&self, frame: Frame) -> bool {
+        let mut latest = self.latest.lock().unwrap();
+        if latest.closed {
+            return false;
+        }
+        latest.frame = Some(frame);
+        self.changed.notify_one();
+        true
+    }
+
+    // take waits for a frame, there are none after the slot is closed
+    fn take(&self) -> Option<Frame> {
+        let mut latest = self.latest.lock().unwrap();
+        loop {
+            if latest.closed {
+                return None;
+            }
+            if let Some(frame) = latest.frame.take() {
+                return Some(frame);
+            }
+            latest = self.changed.wait(latest).unwrap();
+        }
+    }
+
+    fn close(&self) {
+        self.latest.lock().unwrap().closed = true;
+        self.changed.notify_one();
+    }
+}
+
+// addresses with a port are TCP ones,
+// anything else is a path to a Unix socket
+fn is_tcp(addr: &str) -> bool {
+    addr.contains(':')
+}
+
+// Publisher streams boards of the running game to everybody watching it
+pub struct Publisher {
+    watchers: Watchers,
+    // somebody started watching since the last frame
+    newcomers: Arc<AtomicBool>,
+    socket: Option<PathBuf>,
+}
+
+impl Publisher {
+    pub fn bind(addr: &str) -> io::Result<Publisher> {
+        let watchers = Watchers::default();
+        let newcomers = Arc::new(AtomicBool::new(false));
+        let accepted = Arc::clone(&watchers);
+        let came = Arc::clone(&newcomers);
+
+        if is_tcp(addr) {
+            let listener = TcpListener::bind(addr)?;
+            thread::spawn(move || {
+                for s in listener.incoming().flatten() {
+                    if s.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                        accepted.lock().unwrap().push(writer(s));
+                        came.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+
+            return Ok(Publisher {
+                watchers,
+                newcomers,
+                socket: None,
+            });
+        }
+
+        // socket left behind by a previous game is in the way,
+        // but we don't remove anything else
+        let path = PathBuf::from(addr);
+        if let Ok(m) = fs::symlink_metadata(&path) {
+            if m.file_type().is_socket() {
+                fs::remove_file(&path)?;
+            }
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        thread::spawn(move || {
+            for s in listener.incoming().flatten() {
+                if s.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                    accepted.lock().unwrap().push(writer(s));
+                    came.store(true, Ordering::Relaxed);
+                }
+            }
+        });
+
+        Ok(Publisher {
+            watchers,
+            newcomers,
+            socket: Some(path),
+        })
+    }
+
+    // has_newcomers tells that somebody started watching and
+    // waits for a frame, the game may not change for a long time
+    pub fn has_newcomers(&self) -> bool {
+        self.newcomers.load(Ordering::Relaxed)
+    }
+
+    // publish hands boards to every watcher without waiting for them,
+    // no boards means that nothing is played at the moment
+    pub fn publish(&self, boards: &[&Board]) {
+        self.newcomers.store(false, Ordering::Relaxed);
+        let mut watchers = self.watchers.lock().unwrap();
+        if watchers.is_empty() {
+            return;
+        }
+
+        // frame is encoded once for all of them
+        let mut frame = Vec::new();
+        if net::write_message(&mut frame, &boards).is_err() {
+            return;
+        }
+
+        let frame = Arc::new(frame);
+        watchers.retain(|w| w.put(Arc::clone(&frame)));
+    }
+}
+
+// writer writes frames to the watcher on its own thread, so that
+// a slow watcher never holds up the game, it stops once writing fails
+fn writer<W: Write + Send + 'static>(mut w: W) -> Arc<Slot> {
+    let slot = Arc::new(Slot::default());
+    let taken = Arc::clone(&slot);
+    thread::spawn(move || {
+        while let Some(frame) = taken.take() {
+            if w.write_all(&frame).is_err() {
+                taken.close();
+                return;
+            }
+        }
+    });
+    slot
+}
+
+impl Drop for Publisher {
+    fn drop(&mut self) {
+        for w in self.watchers.lock().unwrap().iter() {
+            w.close();
+        }
+        if let Some(path) = &self.socket {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+// watch connects to a published game, boards arrive on the channel
+// as they change, and the channel is closed when the stream ends
+pub fn watch(addr: &str) -> io::Result<mpsc::Receiver<Vec<Board>>> {
+    let stream: Box<dyn Read + Send> = if is_tcp(addr) {
+        Box::new(TcpStream::connect(addr)?)
+    } else {
+        Box::new(UnixStream::connect(addr)?)
+    };
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stream = io::BufReader::new(stream);
+        while let Ok(boards) = net::read_message::<_, Vec<Board>>(&mut stream) {
+            // boards are drawn as they are, ones which can't be are skipped
+            if boards.iter().any(|b| b.check().is_err()) {
+                continue;
+            }
+            if tx.send(boards).is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::settings::Settings;
+    use std::env;
+    use std::time::Instant;
+
+    fn socket(name: &str) -> String {
+        let path = env::temp_dir().join(format!("tetetris-{}-{}", name, std::process::id()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn stalled_watcher_holds_nobody_up() {
+        let addr = socket("stalled");
+        let publisher = Publisher::bind(&addr).unwrap();
+        // this one never reads anything
+        let _stalled = UnixStream::connect(&addr).unwrap();
+        let frames = watch(&addr).unwrap();
+
+        let board = Board::new(Settings::new(300));
+        while publisher.watchers.lock().unwrap().len() < 2 {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let start = Instant::now();
+        for _ in 0..500 {
+            publisher.publish(&[&board]);
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(publisher.watchers.lock().unwrap().len(), 2);
+
+        let boards = frames.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(boards.len(), 1);
+    }
+
+    #[test]
+    fn slow_watcher_gets_the_latest_frame() {
+        let addr = socket("slow");
+        let publisher = Publisher::bind(&addr).unwrap();
+        let mut slow = io::BufReader::new(UnixStream::connect(&addr).unwrap());
+        while publisher.watchers.lock().unwrap().is_empty() {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // the socket fills up long before the last frame
+        let board = Board::new(Settings::new(300));
+        for _ in 0..2000 {
+            publisher.publish(&[&board]);
+        }
+        publisher.publish(&[&board, &board]);
+
+        slow.get_ref()
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        loop {
+            let boards: Vec<Board> = net::read_message(&mut slow).unwrap();
+            if boards.len() == 2 {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn boards_which_cant_be_drawn_are_skipped() {
+        let addr = socket("broken");
+        let _ = fs::remove_file(&addr);
+        let listener = UnixListener::bind(&addr).unwrap();
+        let frames = watch(&addr).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let broken = Board::new(Settings {
+            preview: 50_000,
+            ..Settings::new(300)
+        });
+        let fine = Board::new(Settings::new(300));
+        net::write_message(&mut stream, &[&broken]).unwrap();
+        net::write_message(&mut stream, &[&fine]).unwrap();
+
+        let boards = frames.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(boards[0].settings().preview, 1);
+        let _ = fs::remove_file(&addr);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::game::{Cell, Type};
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::env;
+use std::error::Error;
+use tui::style::{Color, Modifier, Style};
+
+const BUILTIN: &str = include_str!("themes.toml");
+
+#[derive(Deserialize)]
+struct Builtin {
+    themes: Vec<Theme>,
+}
+
+// Theme tells colors of everything drawn, except for
+// the terminal's own text and background
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    pub name: String,
+    // piece colors, several sets are switched with the level
+    pieces: Vec<Pieces>,
+    garbage: Hue,
+    // box titles
+    title: Hue,
+    // score and selected menu items
+    highlight: Hue,
+    good: Hue,
+    bad: Hue,
+    // garbage waiting to enter the field
+    pending: Hue,
+    // background of messages drawn over the field
+    overlay: Hue,
+    // hints and other less important text
+    dim: Hue,
+
+    // no colors at all, pieces are told apart by their glyphs
+    #[serde(skip)]
+    monochrome: bool,
+    #[serde(skip)]
+    cells: CellStyle,
+}
+
+// CellStyle is how cells of the field are drawn
+#[derive(Copy, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CellStyle {
+    // background color
+    #[default]
+    Block,
+    // [] in the piece color
+    Brackets,
+    // full block chars in the piece color
+    Full,
+    // half block chars, two rows in a line
+    Half,
+    // braille dots, 2x4 cells in a char
+    Braille,
+}
+
+impl CellStyle {
+    pub const NAMES: [&'static str; 5] = ["block", "brackets", "full", "half", "braille"];
+
+    pub fn from_name(name: &str) -> Option<CellStyle> {
+        let all = [
+            CellStyle::Block,
+            CellStyle::Brackets,
+            CellStyle::Full,
+            CellStyle::Half,
+            CellStyle::Braille,
+        ];
+        CellStyle::NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|i| all[i])
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Pieces {
+    i: Hue,
+    l: Hue,
+    t: Hue,
+    o: Hue,
+    s: Hue,
+    j: Hue,
+    z: Hue,
+}
+
+// Hue is a color written in a theme: a name like "Cyan",
+// "#rrggbb" or a number of the color on 256 color terminals
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+struct Hue(Color);
+
+impl TryFrom<String> for Hue {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Hue, String> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let rgb = u32::from_str_radix(hex, 16).map_err(|_| format!("bad color {}", s))?;
+            if hex.len() != 6 {
+                return Err(format!("bad color {}", s));
+            }
+            let [_, r, g, b] = rgb.to_be_bytes();
+            return Ok(Hue(Color::Rgb(r, g, b)));
+        }
+        if let Ok(i) = s.parse() {
+            return Ok(Hue(Color::Indexed(i)));
+        }
+
+        let name: serde::de::value::StrDeserializer<serde::de::value::Error> =
+            s.as_str().into_deserializer();
+        Color::deserialize(name)
+            .map(Hue)
+            .map_err(|_| format!("unknown color {}", s))
+    }
+}
+
+impl Theme {
+    pub fn builtin() -> Vec<Theme> {
+        let b: Builtin = toml::from_str(BUILTIN).expect("built-in themes are broken");
+        b.themes
+    }
+
+    // find picks the theme by its name from the built-in ones and
+    // the ones from the config, monochrome is also forced by NO_COLOR
+    pub fn find(
+        name: Option<&str>,
+        custom: &[Theme],
+        monochrome: bool,
+    ) -> Result<Theme, Box<dyn Error>> {
+        let mut themes = Theme::builtin();
+        themes.extend(custom.iter().cloned());
+
+        // custom themes can replace built-in ones with the same name
+        let found = match name {
+            Some(name) => themes
+                .iter()
+                .rposition(|t| t.name.eq_ignore_ascii_case(name)),
+            None => Some(0),
+        };
+        let mut theme = match found {
+            Some(i) => themes.swap_remove(i),
+            None => {
+                let names: Vec<&str> = themes.iter().map(|t| t.name.as_str()).collect();
+                return Err(format!(
+                    "There is no theme '{}', available are: {}",
+                    name.unwrap_or_default(),
+                    names.join(", ")
+                )
+                .into());
+            }
+        };
+
+        if theme.pieces.is_empty() {
+            return Err(format!("Theme '{}' has no piece colors", theme.name).into());
+        }
+
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        theme.monochrome = monochrome || no_color;
+        Ok(theme)
+    }
+
+    pub fn with_cells(self, cells: CellStyle) -> Theme {
+        Theme { cells, ..self }
+    }
+
+    pub fn is_monochrome(&self) -> bool {
+        self.monochrome
+    }
+
+    pub fn cells(&self) -> CellStyle {
+        self.cells
+    }
+
+    // faint is the color of cells which are not really there
+    pub fn faint(&self) -> Color {
+        self.dim.0
+    }
+
+    pub fn cell(&self, c: Cell, level: u32) -> Color {
+        match c {
+            Cell::Piece(t) => self.piece(t, level),
+            Cell::Garbage => self.garbage.0,
+        }
+    }
+
+    pub fn piece(&self, t: Type, level: u32) -> Color {
+        let p = &self.pieces[level.saturating_sub(1) as usize % self.pieces.len()];
+        match t {
+            Type::I => p.i.0,
+            Type::L => p.l.0,
+            Type::T => p.t.0,
+            Type::O => p.o.0,
+            Type::S => p.s.0,
+            Type::J => p.j.0,
+            Type::Z => p.z.0,
+        }
+    }
+
+    pub fn title(&self) -> Style {
+        self.fg(self.title.0).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn highlight(&self) -> Style {
+        self.fg(self.highlight.0).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn good(&self) -> Style {
+        self.fg(self.good.0)
+    }
+
+    pub fn bad(&self) -> Style {
+        self.fg(self.bad.0)
+    }
+
+    pub fn dim(&self) -> Style {
+        self.fg(self.dim.0)
+    }
+
+    // overlay is left as is in monochrome,
+    // reversed message box would be hard to read
+    pub fn overlay(&self) -> Style {
+        if self.monochrome {
+            Style::default()
+        } else {
+            Style::default().bg(self.overlay.0)
+        }
+    }
+
+    pub fn pending(&self) -> Style {
+        self.bg(self.pending.0)
+    }
+
+    pub fn ready(&self) -> Style {
+        self.bg(self.bad.0)
+    }
+
+    pub fn fg(&self, c: Color) -> Style {
+        if self.monochrome {
+            Style::default()
+        } else {
+            Style::default().fg(c)
+        }
+    }
+
+    pub fn bg(&self, c: Color) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(c)
+        }
+    }
+}
+
+// glyph is the pattern of a cell in monochrome mode,
+// it is repeated to fill the cell
+pub fn glyph(c: Cell) -> &'static str {
+    match c {
+        Cell::Piece(Type::I) => "██",
+        Cell::Piece(Type::L) => "▓▓",
+        Cell::Piece(Type::T) => "▒▒",
+        Cell::Piece(Type::O) => "[]",
+        Cell::Piece(Type::S) => "//",
+        Cell::Piece(Type::J) => "()",
+        Cell::Piece(Type::Z) => "\\\\",
+        Cell::Garbage => "##",
+    }
+}
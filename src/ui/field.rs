@@ -0,0 +1,138 @@
+use super::grid::{self, Grid, Look};
+use crate::game::{Board, Cell};
+use crate::theme::{CellStyle, Theme};
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::Span;
+use tui::widgets::{Block, Borders, Widget};
+
+// Field is the playfield with its border, cells are written
+// straight into the buffer in one go over the board
+pub struct Field<'a> {
+    board: &'a Board,
+    theme: &'a Theme,
+    title: &'a str,
+    grid: Grid,
+}
+
+impl<'a> Field<'a> {
+    pub fn new(board: &'a Board, theme: &'a Theme, title: &'a str, grid: Grid) -> Field<'a> {
+        Field {
+            board,
+            theme,
+            title,
+            grid,
+        }
+    }
+
+    fn render_look(&self, rect: Rect, look: Look, level: u32, buf: &mut Buffer) {
+        let theme = self.theme;
+        match look {
+            Look::Empty => {}
+            Look::Filled(c) => fill(buf, rect, theme, c, level),
+            Look::Mistake(c) => {
+                fill(buf, rect, theme, c, level);
+                // the locked cell is crossed out
+                let style = theme.fg(Color::Black).add_modifier(Modifier::BOLD);
+                let mark = if rect.width >= 4 { " XX " } else { "XX" };
+                let y = rect.y + rect.height / 2;
+                buf.set_stringn(rect.x, y, mark, rect.width as usize, style);
+            }
+            Look::Hint(t) => {
+                // outlined with box borders when cell is big enough for them
+                let style = theme.fg(theme.piece(t, level));
+                if rect.height >= 2 {
+                    let b = Block::default().borders(Borders::ALL).border_style(style);
+                    b.render(rect, buf);
+                } else {
+                    pattern(buf, rect, "[]", style);
+                }
+            }
+            // faint pattern showing where the trainer shape wants a piece
+            Look::Target(t) => pattern(buf, rect, "░", theme.fg(theme.piece(t, level))),
+        }
+    }
+}
+
+impl Widget for Field<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(self.title, self.theme.title()));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // board is hidden on pause, so that it can't be used to plan
+        let looks = if self.board.is_paused() {
+            [[Look::Empty; 10]; 20]
+        } else {
+            grid::looks(self.board)
+        };
+        let level = self.board.level();
+
+        if self.grid.is_dense() {
+            let lines = match self.grid.style {
+                CellStyle::Half => {
+                    grid::half_lines(&looks, self.theme, level, self.grid.cell_width)
+                }
+                _ => grid::braille_lines(&looks, self.theme, level),
+            };
+            for (y, line) in (inner.y..inner.bottom()).zip(&lines) {
+                buf.set_spans(inner.x, y, line, inner.width);
+            }
+            return;
+        }
+
+        let (cell_width, cell_height) = (self.grid.cell_width, self.grid.cell_height);
+        for (y, row) in looks.iter().enumerate() {
+            for (x, look) in row.iter().enumerate() {
+                let rect = Rect::new(
+                    inner.x + x as u16 * cell_width,
+                    inner.y + y as u16 * cell_height,
+                    cell_width,
+                    cell_height,
+                );
+                self.render_look(rect.intersection(inner), *look, level, buf);
+            }
+        }
+    }
+}
+
+// Square is a single cell drawn outside of the field,
+// like the upcoming pieces or opponent's board
+pub struct Square<'a> {
+    theme: &'a Theme,
+    cell: Cell,
+    level: u32,
+}
+
+impl<'a> Square<'a> {
+    pub fn new(theme: &'a Theme, cell: Cell, level: u32) -> Square<'a> {
+        Square { theme, cell, level }
+    }
+}
+
+impl Widget for Square<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        fill(buf, area, self.theme, self.cell, self.level);
+    }
+}
+
+// fill paints a cell with the color of its piece, or with
+// the glyph of the cell style, or the piece glyph when colors are off
+fn fill(buf: &mut Buffer, rect: Rect, theme: &Theme, c: Cell, level: u32) {
+    match grid::cell_glyph(theme, c) {
+        Some(glyph) => pattern(buf, rect, glyph, theme.fg(theme.cell(c, level))),
+        None => buf.set_style(rect, Style::default().bg(theme.cell(c, level))),
+    }
+}
+
+// pattern repeats the pattern over the whole rect
+fn pattern(buf: &mut Buffer, rect: Rect, pattern: &str, style: Style) {
+    let times = rect.width as usize / pattern.chars().count();
+    let line = pattern.repeat(times);
+    for y in rect.y..rect.bottom() {
+        buf.set_stringn(rect.x, y, &line, rect.width as usize, style);
+    }
+}
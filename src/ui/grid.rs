@@ -0,0 +1,190 @@
+use crate::game::{Board, Cell, Type};
+use crate::theme::{CellStyle, Theme};
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+
+// Grid is how the field is put on the terminal: cell style
+// and the amount of terminal cells one board cell takes
+#[derive(Copy, Clone)]
+pub struct Grid {
+    pub style: CellStyle,
+    pub cell_width: u16,
+    pub cell_height: u16,
+}
+
+impl Grid {
+    // candidates are grids to try from the biggest one, styles drawing
+    // a cell per board cell fall back to the denser ones when
+    // terminal is too small for them
+    pub fn candidates(style: CellStyle) -> Vec<Grid> {
+        let grid = |style, cell_width, cell_height| Grid {
+            style,
+            cell_width,
+            cell_height,
+        };
+
+        match style {
+            CellStyle::Braille => vec![grid(CellStyle::Braille, 1, 1)],
+            CellStyle::Half => vec![grid(CellStyle::Half, 2, 1), grid(CellStyle::Braille, 1, 1)],
+            s => vec![
+                grid(s, 8, 4),
+                grid(s, 6, 3),
+                grid(s, 4, 2),
+                grid(s, 2, 1),
+                grid(CellStyle::Half, 2, 1),
+                grid(CellStyle::Braille, 1, 1),
+            ],
+        }
+    }
+
+    // inner_size is the size of the field without borders
+    pub fn inner_size(&self) -> (u16, u16) {
+        match self.style {
+            // two rows in a line
+            CellStyle::Half => (10 * self.cell_width, 10),
+            // 2x4 cells in a braille char
+            CellStyle::Braille => (5, 5),
+            _ => (10 * self.cell_width, 20 * self.cell_height),
+        }
+    }
+
+    pub fn field_size(&self) -> (u16, u16) {
+        let (width, height) = self.inner_size();
+        (width + 2, height + 2)
+    }
+
+    // is_dense tells whether several board cells share a terminal cell,
+    // outlines and marks can't be drawn then
+    pub fn is_dense(&self) -> bool {
+        matches!(self.style, CellStyle::Half | CellStyle::Braille)
+    }
+}
+
+// Look is what one cell of the field shows
+#[derive(Copy, Clone)]
+pub enum Look {
+    Empty,
+    Filled(Cell),
+    // locked cell the trainer shape doesn't want there
+    Mistake(Cell),
+    Hint(Type),
+    Target(Type),
+}
+
+// looks goes over the board once, so that drawing
+// doesn't have to ask the board about every cell
+pub fn looks(board: &Board) -> [[Look; 10]; 20] {
+    let mut looks = [[Look::Empty; 10]; 20];
+    for (y, row) in board.field().iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            let (x8, y8) = (x as i8, y as i8);
+            looks[y][x] = match cell {
+                Some(c) if board.is_mistake(x8, y8) => Look::Mistake(*c),
+                Some(c) => Look::Filled(*c),
+                None => board
+                    .hint_pixel(x8, y8)
+                    .map(Look::Hint)
+                    .or_else(|| board.target_pixel(x8, y8).map(Look::Target))
+                    .unwrap_or(Look::Empty),
+            };
+        }
+    }
+    looks
+}
+
+// half_lines renders the field with half block chars,
+// upper half is one row and lower half is the next one
+pub fn half_lines(
+    looks: &[[Look; 10]; 20],
+    theme: &Theme,
+    level: u32,
+    cell_width: u16,
+) -> Vec<Spans<'static>> {
+    looks
+        .chunks(2)
+        .map(|rows| {
+            let spans: Vec<Span> = rows[0]
+                .iter()
+                .zip(&rows[1])
+                .map(|(upper, lower)| {
+                    let upper = look_color(*upper, theme, level);
+                    let lower = look_color(*lower, theme, level);
+                    half_span(upper, lower, theme, cell_width)
+                })
+                .collect();
+            Spans::from(spans)
+        })
+        .collect()
+}
+
+fn half_span(
+    upper: Option<Color>,
+    lower: Option<Color>,
+    theme: &Theme,
+    width: u16,
+) -> Span<'static> {
+    let (c, style) = match (upper, lower) {
+        (None, None) => (' ', Style::default()),
+        (Some(u), None) => ('▀', theme.fg(u)),
+        (None, Some(l)) => ('▄', theme.fg(l)),
+        (Some(u), Some(l)) if theme.is_monochrome() || u == l => ('█', theme.fg(u)),
+        (Some(u), Some(l)) => ('▀', Style::default().fg(u).bg(l)),
+    };
+    Span::styled(c.to_string().repeat(width as usize), style)
+}
+
+// braille_lines renders the field with braille chars, each of them
+// has 2x4 dots and takes color of the first cell in it
+pub fn braille_lines(looks: &[[Look; 10]; 20], theme: &Theme, level: u32) -> Vec<Spans<'static>> {
+    // dots of a braille char are numbered by columns
+    // for the first 3 rows, the last row goes after them
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+    looks
+        .chunks(4)
+        .map(|rows| {
+            let spans: Vec<Span> = (0..5)
+                .map(|col| {
+                    let mut dots = 0;
+                    let mut color = None;
+                    for (row, row_dots) in rows.iter().zip(&DOTS) {
+                        for (dx, dot) in row_dots.iter().enumerate() {
+                            if let Look::Filled(c) | Look::Mistake(c) = row[2 * col + dx] {
+                                dots |= dot;
+                                color.get_or_insert(theme.cell(c, level));
+                            }
+                        }
+                    }
+
+                    let c = std::char::from_u32(0x2800 + dots).unwrap_or(' ');
+                    let style = color.map(|c| theme.fg(c)).unwrap_or_default();
+                    Span::styled(c.to_string(), style)
+                })
+                .collect();
+            Spans::from(spans)
+        })
+        .collect()
+}
+
+// hints and targets are shown dimmed, there is no room for outlines,
+// and not at all without colors as they would look like pieces then
+fn look_color(look: Look, theme: &Theme, level: u32) -> Option<Color> {
+    match look {
+        Look::Filled(c) | Look::Mistake(c) => Some(theme.cell(c, level)),
+        Look::Hint(_) | Look::Target(_) if !theme.is_monochrome() => Some(theme.faint()),
+        _ => None,
+    }
+}
+
+// cell_glyph is what a cell is filled with in styles drawing
+// chars instead of the background, None for the background fill
+pub fn cell_glyph(theme: &Theme, c: Cell) -> Option<&'static str> {
+    if theme.is_monochrome() {
+        return Some(crate::theme::glyph(c));
+    }
+    match theme.cells() {
+        CellStyle::Brackets => Some("[]"),
+        CellStyle::Full => Some("██"),
+        _ => None,
+    }
+}
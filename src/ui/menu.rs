@@ -0,0 +1,152 @@
+use super::{format_duration, versus, HELP_LINES};
+use crate::game::settings::Mode;
+use crate::menu::{self, Menu, Screen};
+use crate::scores::HighScores;
+use crate::theme::Theme;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Table};
+use tui::Frame;
+
+const MENU_WIDTH: u16 = 48;
+const MENU_HEIGHT: u16 = 20;
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, m: &Menu, scores: &HighScores, theme: &Theme) {
+    let rect = centered(f.size(), MENU_WIDTH, MENU_HEIGHT);
+
+    let title = match m.screen() {
+        Screen::Main => " TeTetris ".to_string(),
+        Screen::Options => " Options ".to_string(),
+        Screen::HighScores => format!(" High scores: {} ", m.scores_mode().name()),
+        Screen::Controls => " Controls ".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(title, theme.title()));
+    let inner = block.inner(rect);
+    f.render_widget(block, rect);
+
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(0), Constraint::Length(2)])
+        .split(inner);
+
+    let hint = match m.screen() {
+        Screen::Main => draw_main(f, parts[0], m, theme),
+        Screen::Options => draw_options(f, parts[0], m, theme),
+        Screen::HighScores => draw_scores(f, parts[0], m, scores, theme),
+        Screen::Controls => draw_controls(f, parts[0]),
+    };
+
+    let hint = Paragraph::new(hint).style(theme.dim());
+    f.render_widget(hint, parts[1]);
+}
+
+fn draw_main<B: Backend>(f: &mut Frame<B>, rect: Rect, m: &Menu, theme: &Theme) -> &'static str {
+    let items: Vec<ListItem> = Mode::ALL
+        .iter()
+        .map(|mode| mode.name())
+        .chain(menu::MAIN_EXTRA.iter().copied())
+        .map(ListItem::new)
+        .collect();
+
+    draw_list(f, rect, items, m.selected(), theme);
+
+    match Mode::ALL.get(m.selected()) {
+        Some(mode) => mode.description(),
+        None if m.selected() == Mode::ALL.len() => Mode::Versus.description(),
+        None => "↑ ↓ to choose, ret to select",
+    }
+}
+
+fn draw_options<B: Backend>(f: &mut Frame<B>, rect: Rect, m: &Menu, theme: &Theme) -> &'static str {
+    let items: Vec<ListItem> = menu::OPTIONS
+        .iter()
+        .enumerate()
+        .map(|(i, name)| ListItem::new(format!("{:<10} < {} >", name, m.option_value(i))))
+        .collect();
+
+    draw_list(f, rect, items, m.option(), theme);
+
+    "← → to change, esc to go back"
+}
+
+fn draw_scores<B: Backend>(
+    f: &mut Frame<B>,
+    rect: Rect,
+    m: &Menu,
+    scores: &HighScores,
+    theme: &Theme,
+) -> &'static str {
+    let header = Row::new(vec!["#", "Score", "Lines", "Time"]).style(theme.highlight());
+
+    let rows: Vec<Row> = scores
+        .top(m.scores_mode())
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            Row::new(vec![
+                (i + 1).to_string(),
+                e.score.to_string(),
+                e.lines.to_string(),
+                format_duration(e.time),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(3),
+        Constraint::Length(10),
+        Constraint::Length(6),
+        Constraint::Length(10),
+    ];
+    let table = Table::new(rows).header(header).widths(&widths);
+    f.render_widget(table, rect);
+
+    "← → to switch mode, esc to go back"
+}
+
+fn draw_controls<B: Backend>(f: &mut Frame<B>, rect: Rect) -> &'static str {
+    let lines: Vec<Spans> = HELP_LINES[1..]
+        .iter()
+        .chain(&[""])
+        .chain(versus::HELP_LINES.iter())
+        .map(|l| Spans::from(*l))
+        .collect();
+    f.render_widget(Paragraph::new(lines), rect);
+
+    "esc to go back"
+}
+
+pub(super) fn draw_list<B: Backend>(
+    f: &mut Frame<B>,
+    rect: Rect,
+    items: Vec<ListItem>,
+    selected: usize,
+    theme: &Theme,
+) {
+    let list = List::new(items)
+        .highlight_style(theme.highlight())
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    state.select(Some(selected));
+
+    f.render_stateful_widget(list, rect, &mut state);
+}
+
+// centered returns rect of given size in the middle of the area,
+// shrinking it if area is not big enough
+pub(super) fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
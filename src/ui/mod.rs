@@ -0,0 +1,472 @@
+use crate::game::settings::{self, Mode};
+use crate::game::{Board, Cell, Pixel, Type};
+use crate::theme::Theme;
+use field::{Field, Square};
+use grid::Grid;
+use std::time::Duration;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::style::Modifier;
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+
+mod field;
+mod grid;
+pub mod menu;
+pub mod online;
+pub mod pause;
+pub mod versus;
+
+const HELP_LEN: usize = 11;
+const HELP_LINES: [&str; HELP_LEN] = [
+    "HELP:",
+    "Directional keys: ← → ↓",
+    "Rotate clockwise: ↑ space x",
+    "Rotate counterclockwize: z",
+    "Hard drop: ret",
+    "Pause: esc",
+    "Hint: h",
+    "Export fumen: f",
+    "Undo, redo (practice): u r",
+    "Save and menu: q",
+    "Quit: C-c",
+];
+
+// INFO_WIDTH is what the score and stats panel needs beside the field
+const INFO_WIDTH: u16 = 26;
+// COMPACT_HEIGHT is the score line and a row of previews,
+// shown above the field when there is no room beside it
+const COMPACT_HEIGHT: u16 = 4;
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, game_board: &Board, theme: &Theme) {
+    let term_rect = f.size();
+
+    // the biggest grid wins, it is better to have the panel
+    // stacked above the field than to shrink the field for it
+    let arrange = |g: Grid| {
+        let (width, height) = g.field_size();
+        if width + 1 + INFO_WIDTH <= term_rect.width && height <= term_rect.height {
+            Some((g, false))
+        } else if width < term_rect.width && height + COMPACT_HEIGHT <= term_rect.height {
+            Some((g, true))
+        } else {
+            None
+        }
+    };
+    let (grid, compact) = match Grid::candidates(theme.cells())
+        .into_iter()
+        .find_map(arrange)
+    {
+        Some(a) => a,
+        None => {
+            draw_too_small(f, theme);
+            return;
+        }
+    };
+
+    let title = format!(" TeTetris: {} ", game_board.settings().mode.name());
+    if compact {
+        draw_compact(f, term_rect, game_board, theme, &title, grid);
+    } else {
+        draw_wide(f, term_rect, game_board, theme, &title, grid);
+    }
+}
+
+// draw_wide puts the panel right to the field and the help left to it,
+// the field is kept in the middle as long as the panel has room
+fn draw_wide<B: Backend>(
+    f: &mut Frame<B>,
+    term_rect: Rect,
+    game_board: &Board,
+    theme: &Theme,
+    title: &str,
+    grid: Grid,
+) {
+    let (board_width, board_height) = grid.field_size();
+
+    let centered = term_rect.x + (term_rect.width - board_width) / 2;
+    let x = centered.min(term_rect.right() - INFO_WIDTH - board_width);
+    let y = term_rect.y + (term_rect.height - board_height) / 2;
+    let field = Rect::new(x, y, board_width, board_height);
+
+    draw_field(f, field, game_board, theme, title, grid);
+
+    let meter = Rect::new(field.x - 1, field.y + 1, 1, field.height - 2);
+    draw_meter(f, meter, game_board, theme);
+
+    let right_pad = Rect::new(
+        field.right(),
+        y,
+        term_rect.right() - field.right(),
+        board_height,
+    );
+    draw_info(f, right_pad, game_board, theme);
+
+    let left_pad = Rect::new(term_rect.x, y, meter.x - term_rect.x, board_height);
+    draw_help(f, left_pad);
+}
+
+// draw_compact stacks the score and previews above the field,
+// stats and help are left out as there is no room for them
+fn draw_compact<B: Backend>(
+    f: &mut Frame<B>,
+    term_rect: Rect,
+    game_board: &Board,
+    theme: &Theme,
+    title: &str,
+    grid: Grid,
+) {
+    let (board_width, board_height) = grid.field_size();
+
+    let x = (term_rect.x + (term_rect.width - board_width) / 2).max(term_rect.x + 1);
+    let y = term_rect.y + (term_rect.height - board_height - COMPACT_HEIGHT) / 2;
+    let panel = Rect::new(x, y, board_width, COMPACT_HEIGHT);
+    let field = Rect::new(x, y + COMPACT_HEIGHT, board_width, board_height);
+
+    draw_field(f, field, game_board, theme, title, grid);
+
+    let meter = Rect::new(field.x - 1, field.y + 1, 1, field.height - 2);
+    draw_meter(f, meter, game_board, theme);
+
+    let score = Paragraph::new(Spans::from(vec![
+        Span::styled(format!("Score: {}", game_board.score()), theme.highlight()),
+        Span::raw(format!("  Lines: {}", game_board.lines())),
+    ]));
+    f.render_widget(score, Rect::new(panel.x + 1, panel.y, panel.width - 1, 1));
+
+    // previews go in a row, as many as fit
+    let level = game_board.level();
+    let per_row = ((panel.width - 1) / 9) as usize;
+    // pieces to come are hidden on pause, too
+    let per_row = if game_board.is_paused() { 0 } else { per_row };
+    for (i, (t, ps)) in game_board.upcoming_pieces().take(per_row).enumerate() {
+        let r = Rect::new(panel.x + 1 + 9 * i as u16, panel.y + 1, 8, 2);
+        draw_piece(f, r, t, ps, theme, level);
+    }
+}
+
+// draw_help shows the keys only when all of them fit,
+// a cut help is worse than none
+fn draw_help<B: Backend>(f: &mut Frame<B>, rect: Rect) {
+    let width = HELP_LINES
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    if rect.width < width + 2 || rect.height < HELP_LEN as u16 + 2 {
+        return;
+    }
+
+    let constraints = [Constraint::Length(HELP_LEN as u16), Constraint::Min(0)];
+
+    let help_block = Layout::default()
+        .constraints(constraints)
+        .direction(Direction::Vertical)
+        .margin(1)
+        .split(rect)[0];
+
+    let lines = Layout::default()
+        .constraints([Constraint::Length(1); HELP_LEN])
+        .direction(Direction::Vertical)
+        .split(help_block);
+
+    for (line, rect) in HELP_LINES.iter().zip(lines) {
+        let msg_len = line.chars().count();
+        let msg_rect = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(msg_len as u16)])
+            .split(rect)[1];
+
+        let b = Block::default().title(*line);
+        f.render_widget(b, msg_rect);
+    }
+}
+
+fn draw_too_small<B: Backend>(f: &mut Frame<B>, theme: &Theme) {
+    let b = Block::default().title(Span::styled(
+        "Terminal is too small, please resize!",
+        theme.bad().add_modifier(Modifier::BOLD),
+    ));
+    f.render_widget(b, f.size());
+}
+
+// draw_message fills the whole screen with a single message
+pub fn draw_message<B: Backend>(f: &mut Frame<B>, msg: &str, theme: &Theme) {
+    let b = Block::default().title(Span::styled(
+        msg.to_string(),
+        theme.good().add_modifier(Modifier::BOLD),
+    ));
+    f.render_widget(b, f.size());
+}
+
+// draw_field renders the board inside the rect, which must
+// fit the field of the grid together with the border
+fn draw_field<B: Backend>(
+    f: &mut Frame<B>,
+    rect: Rect,
+    game_board: &Board,
+    theme: &Theme,
+    title: &str,
+    grid: Grid,
+) {
+    f.render_widget(Field::new(game_board, theme, title, grid), rect);
+
+    // Now, if game has ended or is paused,
+    // it is time to render end game message on top
+    if game_board.is_game_over() || game_board.is_paused() {
+        draw_overlay(f, rect, game_board, theme);
+    }
+}
+
+// draw_overlay shows how the game ended, or that it is paused,
+// in a box in the upper part of the field
+fn draw_overlay<B: Backend>(f: &mut Frame<B>, rect: Rect, game_board: &Board, theme: &Theme) {
+    let msg_style;
+    let msg;
+    let mode = game_board.settings().mode;
+    if game_board.is_completed() && mode == Mode::Versus {
+        msg = "!   WINNER   !";
+        msg_style = theme.good();
+    } else if game_board.is_completed() && mode == Mode::Puzzle {
+        msg = "!   SOLVED   !";
+        msg_style = theme.good();
+    } else if game_board.is_completed() && mode == Mode::Trainer {
+        let clean = game_board.target().is_none_or(|t| t.mistakes() == 0);
+        msg = if clean {
+            "!  PERFECT!  !"
+        } else {
+            "!    BUILT   !"
+        };
+        msg_style = theme.good();
+    } else if game_board.is_completed() {
+        msg = "!  FINISHED  !";
+        msg_style = theme.good();
+    } else if game_board.is_game_over() && mode == Mode::Puzzle {
+        msg = "!   FAILED   !";
+        msg_style = theme.bad();
+    } else if game_board.is_game_over() {
+        msg = "! GAME  OVER !";
+        msg_style = theme.bad();
+    } else {
+        msg = "    PAUSED    ";
+        msg_style = theme.good();
+    };
+
+    let block_width = msg.chars().count() + 4;
+    if rect.width < block_width as u16 || rect.height < 7 {
+        // field is too small for the box, message takes its middle line
+        let r = Rect::new(rect.x, rect.y + rect.height / 2, rect.width, 1);
+        let p = Paragraph::new(Span::styled(
+            msg.trim(),
+            msg_style.add_modifier(Modifier::BOLD),
+        ))
+        .alignment(Alignment::Center)
+        .style(theme.overlay());
+        f.render_widget(p, r);
+        return;
+    }
+
+    let cs = [
+        Constraint::Length(rect.height / 4),
+        Constraint::Length(5),
+        Constraint::Min(0),
+    ];
+
+    let vmsg = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(cs)
+        .split(rect)[1];
+
+    let pad = (rect.width - block_width as u16) / 2;
+    let hmsg = Layout::default()
+        .constraints([
+            Constraint::Length(pad),
+            Constraint::Length(block_width as u16),
+            Constraint::Length(pad),
+        ])
+        .direction(Direction::Horizontal)
+        .split(vmsg)[1];
+
+    let b = Block::default()
+        .borders(Borders::ALL)
+        .style(theme.overlay());
+    f.render_widget(b, hmsg);
+
+    let title_widget =
+        Block::default().title(Span::styled(msg, msg_style.add_modifier(Modifier::BOLD)));
+
+    let lines = Layout::default()
+        .direction(Direction::Vertical)
+        .vertical_margin(1)
+        .horizontal_margin(2)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(hmsg);
+
+    f.render_widget(title_widget, lines[1]);
+
+    if game_board.is_game_over() {
+        let faults = format!("Faults: {}", game_board.stats().finesse_faults());
+        f.render_widget(Block::default().title(faults), lines[2]);
+    }
+}
+
+// draw_meter renders incoming garbage as a bar growing from the bottom
+// of the one column wide rect, lines entering with the next piece are red
+fn draw_meter<B: Backend>(f: &mut Frame<B>, rect: Rect, game_board: &Board, theme: &Theme) {
+    let (total, ready) = game_board.incoming();
+    // field may be drawn with several rows in a line
+    let lines = |rows: u32| rows.min(20) as u16 * rect.height / 20;
+    let (total, ready) = (lines(total), lines(ready));
+
+    let bottom = rect.y + rect.height;
+    let pending = Rect::new(rect.x, bottom - total, 1, total - ready);
+    let ready = Rect::new(rect.x, bottom - ready, 1, ready);
+
+    f.render_widget(Block::default().style(theme.pending()), pending);
+    f.render_widget(Block::default().style(theme.ready()), ready);
+}
+
+fn draw_info<B: Backend>(f: &mut Frame<B>, rect: Rect, game_board: &Board, theme: &Theme) {
+    let settings = game_board.settings();
+    let preview_height = 3 * settings.preview as u16;
+
+    let right_info = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(preview_height),
+            Constraint::Min(0),
+        ])
+        .split(rect);
+
+    let score_block = Block::default().title(Span::styled(
+        format!("Score: {}", game_board.score()),
+        theme.highlight(),
+    ));
+    f.render_widget(score_block, right_info[0]);
+
+    let previews = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(3); settings.preview])
+        .split(right_info[2]);
+
+    // pieces to come are hidden on pause, too
+    let previews = if game_board.is_paused() {
+        Vec::new()
+    } else {
+        previews
+    };
+    for ((t, ps), r) in game_board.upcoming_pieces().zip(previews) {
+        draw_piece(f, r, t, ps, theme, game_board.level());
+    }
+
+    f.render_widget(Paragraph::new(stats_lines(game_board)), right_info[3]);
+}
+
+fn stats_lines(game_board: &Board) -> Vec<Spans<'static>> {
+    let settings = game_board.settings();
+    let stats = game_board.stats();
+    let elapsed = game_board.elapsed();
+
+    let lines = match settings.mode {
+        Mode::Sprint => format!("Lines: {}/{}", game_board.lines(), settings::SPRINT_LINES),
+        _ => format!("Lines: {}", game_board.lines()),
+    };
+    let time = match settings.mode {
+        Mode::Ultra => {
+            let limit = Duration::from_secs(settings::ULTRA_SECONDS);
+            let left = limit.checked_sub(elapsed).unwrap_or_default();
+            format!("Time left: {}", format_duration(left))
+        }
+        _ => format!("Time: {}", format_duration(elapsed)),
+    };
+
+    // pieces are shown in two rows to keep the panel narrow
+    let pieces: Vec<String> = Type::ALL
+        .iter()
+        .map(|t| format!("{} {:<3}", t.name(), stats.pieces(*t)))
+        .collect();
+
+    let mut lines = vec![
+        format!("Level: {}", game_board.level()),
+        lines,
+        time,
+        format!("PPS: {:.2}", stats.pps(elapsed)),
+        format!("KPP: {:.2}", stats.kpp()),
+        format!("APM: {:.2}", stats.apm(elapsed)),
+        String::new(),
+        format!("Pieces: {}", stats.total_pieces()),
+        pieces[..4].join(" "),
+        pieces[4..].join(" "),
+        String::new(),
+        format!("Singles: {}", stats.singles()),
+        format!("Doubles: {}", stats.doubles()),
+        format!("Triples: {}", stats.triples()),
+        format!("Tetrises: {}", stats.tetrises()),
+        format!("Max combo: {}", stats.max_combo()),
+        format!("Finesse faults: {}", stats.finesse_faults()),
+    ];
+    if let Some(goal) = game_board.goal() {
+        lines.insert(0, format!("Goal: {}", goal.description()));
+    }
+    if let Some(target) = game_board.target() {
+        lines.insert(0, format!("Mistakes: {}", target.mistakes()));
+        lines.insert(0, format!("Setup: {}", target.name));
+    }
+
+    lines.into_iter().map(Spans::from).collect()
+}
+
+// draw_piece renders a tetronimo in its spawn position
+// into a small 4x2 cells rect
+fn draw_piece<B: Backend>(
+    f: &mut Frame<B>,
+    rect: Rect,
+    t: Type,
+    ps: &[Pixel; 4],
+    theme: &Theme,
+    level: u32,
+) {
+    let upcoming_rect = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(8)])
+        .split(rect)[0];
+    let cols = vec![Constraint::Length(2); 4];
+    let rows = vec![Constraint::Length(1); 2];
+
+    let ccols = Layout::default()
+        .constraints(cols)
+        .direction(Direction::Horizontal)
+        .split(upcoming_rect);
+    for (p_x, ccol) in (3..7).zip(ccols) {
+        let crows = Layout::default()
+            .constraints(rows.clone())
+            .direction(Direction::Vertical)
+            .split(ccol);
+
+        for (p_y, cell) in (-2..0).zip(crows) {
+            for p in ps {
+                if p.x == p_x && p.y == p_y {
+                    f.render_widget(Square::new(theme, Cell::Piece(t), level), cell);
+                }
+            }
+        }
+    }
+}
+
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!(
+        "{}:{:02}.{:02}",
+        secs / 60,
+        secs % 60,
+        d.subsec_millis() / 10
+    )
+}
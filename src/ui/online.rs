@@ -0,0 +1,115 @@
+use super::grid::Grid;
+use super::versus::{draw_info, INFO_WIDTH};
+use super::{draw_field, draw_meter, draw_too_small, Square};
+use crate::game::{Board, Snapshot};
+use crate::theme::Theme;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::style::Modifier;
+use tui::text::Span;
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+
+// opponent's board is always drawn small
+const OPPONENT_WIDTH: u16 = 10 * 2 + 2;
+const OPPONENT_HEIGHT: u16 = 20 + 2;
+
+pub fn draw<B: Backend>(
+    f: &mut Frame<B>,
+    board: &Board,
+    opponent: Option<&Snapshot>,
+    status: Option<&str>,
+    theme: &Theme,
+) {
+    let term_rect = f.size();
+
+    // opponent's board is always the same size,
+    // ours can get smaller than it
+    let fits = |g: &Grid| {
+        let (width, height) = g.field_size();
+        width + INFO_WIDTH + OPPONENT_WIDTH <= term_rect.width
+            && height.max(OPPONENT_HEIGHT) <= term_rect.height
+    };
+    let grid = match Grid::candidates(theme.cells()).into_iter().find(fits) {
+        Some(g) => g,
+        None => {
+            draw_too_small(f, theme);
+            return;
+        }
+    };
+
+    let (field_width, field_height) = grid.field_size();
+    let width = field_width + INFO_WIDTH + OPPONENT_WIDTH;
+    let height = field_height.max(OPPONENT_HEIGHT);
+
+    let area = Rect::new(
+        term_rect.x + (term_rect.width - width) / 2,
+        term_rect.y + (term_rect.height - height) / 2,
+        width,
+        height,
+    );
+
+    let parts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(field_width),
+            Constraint::Length(INFO_WIDTH),
+            Constraint::Length(OPPONENT_WIDTH),
+        ])
+        .split(area);
+
+    let field = Rect {
+        height: field_height,
+        ..parts[0]
+    };
+    draw_field(f, field, board, theme, " You ", grid);
+    draw_info(f, parts[1], board, theme);
+
+    let meter = Rect::new(field.x + field.width, field.y + 1, 1, field.height - 2);
+    draw_meter(f, meter, board, theme);
+
+    let opponent_rect = Rect::new(parts[2].x, parts[2].y, OPPONENT_WIDTH, OPPONENT_HEIGHT);
+    draw_opponent(f, opponent_rect, opponent, status, theme);
+}
+
+// draw_opponent renders opponent's board with 2x1 cells
+fn draw_opponent<B: Backend>(
+    f: &mut Frame<B>,
+    rect: Rect,
+    opponent: Option<&Snapshot>,
+    status: Option<&str>,
+    theme: &Theme,
+) {
+    let title = match opponent {
+        Some(s) => format!(" Opponent: {} ", s.score),
+        None => " Opponent ".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(title, theme.title()));
+    f.render_widget(block, rect);
+
+    if let Some(s) = opponent {
+        for &(x, y, c) in &s.cells {
+            let cell = Rect::new(rect.x + 1 + 2 * x as u16, rect.y + 1 + y as u16, 2, 1);
+            // opponent's level is not known, the first palette is used
+            f.render_widget(Square::new(theme, c, 1), cell);
+        }
+    }
+
+    let msg = match (status, opponent) {
+        (Some(status), _) => Some((status, theme.bad())),
+        (None, None) => Some(("Waiting...", theme.good())),
+        (None, Some(s)) if s.completed => Some(("WINNER", theme.good())),
+        (None, Some(s)) if s.game_over => Some(("GAME OVER", theme.bad())),
+        _ => None,
+    };
+
+    if let Some((msg, style)) = msg {
+        let r = Rect::new(rect.x + 1, rect.y + rect.height / 2, rect.width - 2, 1);
+        let p = Paragraph::new(Span::styled(msg, style.add_modifier(Modifier::BOLD)))
+            .alignment(Alignment::Center)
+            .style(theme.overlay());
+        f.render_widget(p, r);
+    }
+}
@@ -0,0 +1,82 @@
+use super::menu::{centered, draw_list};
+use super::{versus, HELP_LINES};
+use crate::game::settings::{Mode, Settings};
+use crate::pause::{self, Page, PauseMenu};
+use crate::theme::Theme;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Clear, ListItem, Paragraph};
+use tui::Frame;
+
+const MIN_WIDTH: u16 = 30;
+
+// draw shows the pause menu in the middle of the screen,
+// over the game which has its board hidden while paused
+pub fn draw<B: Backend>(f: &mut Frame<B>, m: &PauseMenu, settings: &Settings, theme: &Theme) {
+    let (title, lines, hint) = match m.page() {
+        Page::Main => (" Paused ", Vec::new(), "↑ ↓ to choose, ret to select"),
+        Page::Settings => (" Settings ", settings_lines(settings), "esc to go back"),
+        Page::Controls => (" Controls ", controls_lines(settings), "esc to go back"),
+    };
+
+    let height = match m.page() {
+        Page::Main => pause::ITEMS.len(),
+        _ => lines.len(),
+    } as u16;
+    let width = lines
+        .iter()
+        .map(|l| l.chars().count() as u16)
+        .chain([hint.chars().count() as u16])
+        .max()
+        .unwrap_or(0);
+    let rect = centered(f.size(), (width + 4).max(MIN_WIDTH), height + 5);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(title, theme.title()));
+    let inner = block.inner(rect);
+    f.render_widget(Clear, rect);
+    f.render_widget(block, rect);
+
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    if m.page() == Page::Main {
+        let items: Vec<ListItem> = pause::ITEMS.iter().map(|i| ListItem::new(*i)).collect();
+        draw_list(f, parts[0], items, m.selected(), theme);
+    } else {
+        let lines: Vec<Spans> = lines.into_iter().map(Spans::from).collect();
+        f.render_widget(Paragraph::new(lines), parts[0]);
+    }
+
+    f.render_widget(Paragraph::new(hint).style(theme.dim()), parts[1]);
+}
+
+fn settings_lines(s: &Settings) -> Vec<String> {
+    let seed = match s.seed {
+        Some(seed) => seed.to_string(),
+        None => "random".to_string(),
+    };
+
+    vec![
+        format!("Mode: {}", s.mode.name()),
+        format!("Level: {}", s.level),
+        format!("Gravity: {} ms", s.gravity),
+        format!("Preview: {}", s.preview),
+        format!("Rotation: {}", s.rotation.name()),
+        format!("Garbage delay: {} ms", s.garbage_delay),
+        format!("Seed: {}", seed),
+    ]
+}
+
+fn controls_lines(s: &Settings) -> Vec<String> {
+    let lines: &[&str] = match s.mode {
+        Mode::Versus => &versus::HELP_LINES,
+        _ => &HELP_LINES[1..],
+    };
+    lines.iter().map(|l| l.to_string()).collect()
+}
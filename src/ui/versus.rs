@@ -0,0 +1,120 @@
+use super::grid::Grid;
+use super::{draw_field, draw_meter, draw_piece, draw_too_small};
+use crate::game::Board;
+use crate::theme::Theme;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::text::{Span, Spans};
+use tui::widgets::Paragraph;
+use tui::Frame;
+
+pub const HELP_LINES: [&str; 3] = [
+    "Versus, left player: a d s, rotate w e, drop space",
+    "Versus, right player: ← → ↓, rotate ↑ /, drop ret",
+    "Versus, both: pause esc, menu q",
+];
+
+pub const INFO_WIDTH: u16 = 14;
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, boards: [&Board; 2], theme: &Theme) {
+    let term_rect = f.size();
+
+    // two boards with info panels have to fit side by side
+    let fits = |g: &Grid| {
+        let (width, height) = g.field_size();
+        2 * (width + INFO_WIDTH) <= term_rect.width && height <= term_rect.height
+    };
+    let grid = match Grid::candidates(theme.cells()).into_iter().find(fits) {
+        Some(g) => g,
+        None => {
+            draw_too_small(f, theme);
+            return;
+        }
+    };
+
+    let (field_width, field_height) = grid.field_size();
+    let width = 2 * (field_width + INFO_WIDTH);
+
+    let area = Rect::new(
+        term_rect.x + (term_rect.width - width) / 2,
+        term_rect.y + (term_rect.height - field_height) / 2,
+        width,
+        field_height,
+    );
+
+    let parts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(INFO_WIDTH),
+            Constraint::Length(field_width),
+            Constraint::Length(field_width),
+            Constraint::Length(INFO_WIDTH),
+        ])
+        .split(area);
+
+    for (i, board) in boards.iter().enumerate() {
+        let title = format!(" Player {} ", i + 1);
+        let (info, field) = if i == 0 {
+            (parts[0], parts[1])
+        } else {
+            (parts[3], parts[2])
+        };
+
+        draw_field(f, field, board, theme, &title, grid);
+        draw_info(f, info, board, theme);
+
+        // meters go on the outer sides, into the margins of info panels
+        let meter_x = if i == 0 {
+            field.x - 1
+        } else {
+            field.x + field.width
+        };
+        draw_meter(
+            f,
+            Rect::new(meter_x, field.y + 1, 1, field.height - 2),
+            board,
+            theme,
+        );
+    }
+}
+
+pub fn draw_info<B: Backend>(f: &mut Frame<B>, rect: Rect, board: &Board, theme: &Theme) {
+    let preview = board.settings().preview;
+
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Length(3 * preview as u16),
+            Constraint::Min(0),
+        ])
+        .split(rect);
+
+    let lines = vec![
+        Spans::from(Span::styled(
+            format!("Score: {}", board.score()),
+            theme.highlight(),
+        )),
+        Spans::from(format!("Lines: {}", board.lines())),
+        Spans::from(Span::styled(
+            format!("Incoming: {}", board.incoming().0),
+            theme.bad(),
+        )),
+    ];
+    f.render_widget(Paragraph::new(lines), parts[0]);
+
+    let previews = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(3); preview])
+        .split(parts[1]);
+
+    let previews = if board.is_paused() {
+        Vec::new()
+    } else {
+        previews
+    };
+    for ((t, ps), r) in board.upcoming_pieces().zip(previews) {
+        draw_piece(f, r, t, ps, theme, board.level());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::game::settings::{Mode, Settings};
+use crate::game::{Board, Move};
+use crate::keymap::{self, Keymap};
+use rand::{thread_rng, Rng};
+use termion::event::Key;
+
+const KEYMAPS: [Keymap; 2] = [keymap::PLAYER_ONE, keymap::PLAYER_TWO];
+
+// Versus is a game of two players on the same terminal,
+// each of them with their own board and keys
+pub struct Versus {
+    pub boards: [Board; 2],
+}
+
+impl Versus {
+    pub fn new(settings: Settings) -> Versus {
+        // both players get exactly the same pieces
+        let settings = Settings {
+            mode: Mode::Versus,
+            seed: Some(settings.seed.unwrap_or_else(|| thread_rng().gen())),
+            ..settings
+        };
+
+        Versus {
+            boards: [Board::new(settings), Board::new(settings)],
+        }
+    }
+
+    // action returns which player pressed the key and what for
+    pub fn action(&self, key: Key) -> Option<(usize, Move)> {
+        KEYMAPS
+            .iter()
+            .enumerate()
+            .find_map(|(i, k)| k.action(key).map(|m| (i, m)))
+    }
+
+    pub fn toggle_pause(&mut self) {
+        for b in &mut self.boards {
+            b.toggle_pause();
+        }
+    }
+
+    // update sends garbage to the opponents
+    // and finishes the game once somebody tops out
+    pub fn update(&mut self) {
+        let attacks = [self.boards[0].take_attack(), self.boards[1].take_attack()];
+        self.boards[1].receive_garbage(attacks[0]);
+        self.boards[0].receive_garbage(attacks[1]);
+
+        for i in 0..2 {
+            if self.boards[i].is_game_over() && !self.boards[i].is_completed() {
+                self.boards[1 - i].win();
+            }
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.boards.iter().any(|b| b.is_game_over())
+    }
+}